@@ -9,7 +9,17 @@ use bevy::{
     prelude::*,
 };
 
-use crate::screens::Screen;
+use crate::{
+    inventory::Inventory,
+    map::{
+        current_house_layout, ExploredRooms, HeldKeys, House, LoadHouse, MapRoomIndex, RoomId,
+        ShiftHouse,
+    },
+    monster::{Monster, PlaceMonsters},
+    sanity::{CurrentSanityLevel, Sanity},
+    screens::Screen,
+    turns::GameClock,
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Log `Screen` state transitions.
@@ -21,6 +31,25 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         toggle_debug_ui.run_if(input_just_pressed(TOGGLE_KEY)),
     );
+
+    // Write the current house out as JSON, or replace it with the one that was written out, so
+    // hand-made houses can be started from a generated one. Finished houses ship as `.house.json`
+    // files in `assets/houses`.
+    app.add_systems(
+        Update,
+        (
+            export_house.run_if(input_just_pressed(EXPORT_HOUSE_KEY)),
+            import_house.run_if(input_just_pressed(IMPORT_HOUSE_KEY)),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+
+    // Shift the house on demand rather than waiting for it.
+    app.add_systems(
+        Update,
+        shift_house
+            .run_if(in_state(Screen::Gameplay).and_then(input_just_pressed(SHIFT_HOUSE_KEY))),
+    );
 }
 
 const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
@@ -28,3 +57,60 @@ const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
 fn toggle_debug_ui(mut options: ResMut<UiDebugOptions>) {
     options.toggle();
 }
+
+const EXPORT_HOUSE_KEY: KeyCode = KeyCode::F5;
+const IMPORT_HOUSE_KEY: KeyCode = KeyCode::F9;
+const HOUSE_FILE: &str = "house.json";
+
+fn export_house(world: &mut World) {
+    let Some(layout) = current_house_layout(world) else {
+        return;
+    };
+    let result = serde_json::to_string_pretty(&layout)
+        .map_err(|error| error.to_string())
+        .and_then(|json| std::fs::write(HOUSE_FILE, json).map_err(|error| error.to_string()));
+    match result {
+        Ok(()) => info!("Wrote the house to {HOUSE_FILE}"),
+        Err(error) => error!("Failed to write the house to {HOUSE_FILE}: {error}"),
+    }
+}
+
+fn import_house(
+    mut commands: Commands,
+    houses: Query<Entity, With<House>>,
+    monsters: Query<Entity, With<Monster>>,
+) {
+    let layout = std::fs::read_to_string(HOUSE_FILE)
+        .map_err(|error| error.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|error| error.to_string()));
+    let layout = match layout {
+        Ok(layout) => layout,
+        Err(error) => {
+            error!("Failed to read the house from {HOUSE_FILE}: {error}");
+            return;
+        }
+    };
+
+    // Start the imported house over as a new run, so nothing carries over from the last house.
+    for entity in houses.iter().chain(monsters.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.add(LoadHouse {
+        layout,
+        new_run: true,
+    });
+    commands.add(PlaceMonsters { saved: None });
+    commands.insert_resource(MapRoomIndex(RoomId(0)));
+    commands.insert_resource(ExploredRooms::default());
+    commands.insert_resource(HeldKeys::default());
+    commands.insert_resource(Inventory::default());
+    commands.insert_resource(Sanity::default());
+    commands.insert_resource(CurrentSanityLevel::default());
+    commands.insert_resource(GameClock::default());
+}
+
+const SHIFT_HOUSE_KEY: KeyCode = KeyCode::F6;
+
+fn shift_house(mut shift_writer: EventWriter<ShiftHouse>) {
+    shift_writer.send(ShiftHouse { swaps: 2 });
+}
@@ -0,0 +1,248 @@
+//! Moving between rooms with the keyboard or a gamepad.
+//!
+//! Every direction has a list of [`InputBinding`]s that the player can change from the settings
+//! screen. The bindings are kept with the rest of the settings, see [`crate::settings`].
+
+use bevy::{
+    input::gamepad::{GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads},
+    prelude::*,
+    utils::HashSet,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    map::{MoveInDirection, RoomConnectionDirection},
+    screens::Pause,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            record_navigation_input.run_if(in_state(Pause::Running)),
+            record_rebinding.run_if(resource_exists::<Rebinding>),
+        )
+            .in_set(AppSet::RecordInput),
+    );
+}
+
+/// How far a stick has to be pushed before it counts as a move
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// The stick axes that can be bound to a direction
+const STICK_AXES: [GamepadAxisType; 4] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+];
+
+/// A single input that can move the player in a direction
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum InputBinding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+    /// Pushing a stick along an axis, towards the positive or negative end of it
+    GamepadStick {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+impl InputBinding {
+    /// Whether this binding comes from a gamepad rather than the keyboard
+    pub fn is_gamepad(&self) -> bool {
+        !matches!(self, InputBinding::Key(_))
+    }
+
+    /// A short name for the binding to show to the player
+    pub fn label(&self) -> String {
+        match self {
+            InputBinding::Key(key_code) => format!("{key_code:?}"),
+            InputBinding::GamepadButton(button_type) => format!("{button_type:?}"),
+            InputBinding::GamepadStick { axis, positive } => {
+                format!("{axis:?}{}", if *positive { "+" } else { "-" })
+            }
+        }
+    }
+}
+
+/// The inputs that move the player in each direction
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct InputBindings(pub BTreeMap<RoomConnectionDirection, Vec<InputBinding>>);
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use InputBinding::*;
+        use RoomConnectionDirection::*;
+
+        let stick = |axis, positive| GamepadStick { axis, positive };
+        InputBindings(BTreeMap::from([
+            (
+                North,
+                vec![
+                    Key(KeyCode::ArrowUp),
+                    Key(KeyCode::KeyW),
+                    GamepadButton(GamepadButtonType::DPadUp),
+                    stick(GamepadAxisType::LeftStickY, true),
+                ],
+            ),
+            (
+                East,
+                vec![
+                    Key(KeyCode::ArrowRight),
+                    Key(KeyCode::KeyD),
+                    GamepadButton(GamepadButtonType::DPadRight),
+                    stick(GamepadAxisType::LeftStickX, true),
+                ],
+            ),
+            (
+                South,
+                vec![
+                    Key(KeyCode::ArrowDown),
+                    Key(KeyCode::KeyS),
+                    GamepadButton(GamepadButtonType::DPadDown),
+                    stick(GamepadAxisType::LeftStickY, false),
+                ],
+            ),
+            (
+                West,
+                vec![
+                    Key(KeyCode::ArrowLeft),
+                    Key(KeyCode::KeyA),
+                    GamepadButton(GamepadButtonType::DPadLeft),
+                    stick(GamepadAxisType::LeftStickX, false),
+                ],
+            ),
+            (
+                Up,
+                vec![
+                    Key(KeyCode::KeyE),
+                    Key(KeyCode::PageUp),
+                    GamepadButton(GamepadButtonType::RightTrigger),
+                ],
+            ),
+            (
+                Down,
+                vec![
+                    Key(KeyCode::KeyQ),
+                    Key(KeyCode::PageDown),
+                    GamepadButton(GamepadButtonType::LeftTrigger),
+                ],
+            ),
+        ]))
+    }
+}
+
+impl InputBindings {
+    /// The bindings for a direction
+    pub fn get(&self, direction: &RoomConnectionDirection) -> &[InputBinding] {
+        self.0.get(direction).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Bind `binding` to `direction` in place of the direction's other bindings from the same kind
+    /// of device. The binding is taken away from any other direction it was bound to.
+    pub fn rebind(&mut self, direction: RoomConnectionDirection, binding: InputBinding) {
+        for bindings in self.0.values_mut() {
+            bindings.retain(|other| *other != binding);
+        }
+        let bindings = self.0.entry(direction).or_default();
+        bindings.retain(|other| other.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+    }
+}
+
+/// Present while waiting for the player to press the input to bind to a direction
+#[derive(Resource, Debug)]
+pub struct Rebinding(pub RoomConnectionDirection);
+
+/// The stick directions each gamepad is being pushed in
+fn pushed_sticks(
+    gamepads: &Gamepads,
+    axes: &Axis<GamepadAxis>,
+) -> HashSet<(Gamepad, GamepadAxisType, bool)> {
+    gamepads
+        .iter()
+        .flat_map(|gamepad| {
+            STICK_AXES.iter().filter_map(move |axis_type| {
+                let value = axes.get(GamepadAxis::new(gamepad, *axis_type))?;
+                (value.abs() >= STICK_THRESHOLD).then_some((gamepad, *axis_type, value > 0.0))
+            })
+        })
+        .collect()
+}
+
+fn record_navigation_input(
+    bindings: Res<InputBindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    // Sticks don't have a "just pressed", so remember which were already pushed last frame.
+    mut held_sticks: Local<HashSet<(Gamepad, GamepadAxisType, bool)>>,
+    mut move_writer: EventWriter<MoveInDirection>,
+) {
+    let pushed = pushed_sticks(&gamepads, &axes);
+    let just_pressed = |binding: &InputBinding| match *binding {
+        InputBinding::Key(key_code) => keys.just_pressed(key_code),
+        InputBinding::GamepadButton(button_type) => gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type))),
+        InputBinding::GamepadStick { axis, positive } => gamepads.iter().any(|gamepad| {
+            pushed.contains(&(gamepad, axis, positive))
+                && !held_sticks.contains(&(gamepad, axis, positive))
+        }),
+    };
+
+    if let Some((direction, _)) = bindings
+        .0
+        .iter()
+        .find(|(_, bindings)| bindings.iter().any(just_pressed))
+    {
+        move_writer.send(MoveInDirection {
+            direction: direction.clone(),
+        });
+    }
+    *held_sticks = pushed;
+}
+
+/// Bind the first input the player presses to the direction being rebound. Escape cancels.
+fn record_rebinding(
+    mut commands: Commands,
+    rebinding: Res<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        commands.remove_resource::<Rebinding>();
+        return;
+    }
+
+    let binding = keys
+        .get_just_pressed()
+        .next()
+        .map(|key_code| InputBinding::Key(*key_code))
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| InputBinding::GamepadButton(button.button_type))
+        })
+        .or_else(|| {
+            pushed_sticks(&gamepads, &axes)
+                .into_iter()
+                .next()
+                .map(|(_, axis, positive)| InputBinding::GamepadStick { axis, positive })
+        });
+    let Some(binding) = binding else {
+        return;
+    };
+
+    bindings.rebind(rebinding.0.clone(), binding);
+    commands.remove_resource::<Rebinding>();
+}
@@ -0,0 +1,84 @@
+use bevy::{prelude::*, utils::HashMap};
+use leafwing_manifest::{
+    identifier::Id,
+    manifest::{Manifest, ManifestFormat},
+    plugin::RegisterManifest,
+};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_manifest::<ItemManifest>("items.assets.json");
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RawItemDefinition {
+    /// The name the item is referred to by, such as in a prop's `pick-up:<item_name>` interaction.
+    pub item_name: String,
+    /// The name the player sees.
+    pub display_name: String,
+    pub description: String,
+    /// The path of the item's image.
+    pub icon: String,
+    /// How much sanity using the item restores. Items that restore none can't be used.
+    #[serde(default)]
+    pub sanity: f32,
+}
+
+/// Something the player can carry
+#[derive(Debug, Clone)]
+pub struct ItemDefinition {
+    pub item_name: String,
+    pub display_name: String,
+    pub description: String,
+    pub icon: Handle<Image>,
+    /// How much sanity using the item restores
+    pub sanity: f32,
+}
+
+#[derive(Debug, Resource)]
+pub struct ItemManifest {
+    pub items: HashMap<Id<ItemDefinition>, ItemDefinition>,
+}
+#[derive(Debug, Resource, Serialize, Deserialize, Asset, TypePath)]
+pub struct RawItemManifest {
+    items: Vec<RawItemDefinition>,
+}
+impl Manifest for ItemManifest {
+    type RawManifest = RawItemManifest;
+
+    type RawItem = RawItemDefinition;
+
+    type Item = ItemDefinition;
+
+    type ConversionError = std::convert::Infallible;
+
+    const FORMAT: ManifestFormat = ManifestFormat::Json;
+
+    fn from_raw_manifest(
+        raw_manifest: Self::RawManifest,
+        world: &mut World,
+    ) -> Result<Self, Self::ConversionError> {
+        let asset_server = world.resource::<AssetServer>();
+
+        let items: HashMap<_, _> = raw_manifest
+            .items
+            .into_iter()
+            .map(|raw_item| {
+                let item = ItemDefinition {
+                    item_name: raw_item.item_name,
+                    display_name: raw_item.display_name,
+                    description: raw_item.description,
+                    icon: asset_server.load(raw_item.icon),
+                    sanity: raw_item.sanity,
+                };
+                (Id::from_name(&item.item_name), item)
+            })
+            .collect();
+
+        Ok(ItemManifest { items })
+    }
+
+    fn get(&self, id: Id<Self::Item>) -> Option<&Self::Item> {
+        self.items.get(&id)
+    }
+}
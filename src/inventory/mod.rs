@@ -0,0 +1,162 @@
+//! The items the player has collected.
+//!
+//! Items are defined in `items.assets.json`. A prop with a `pick-up:<item_name>` interaction is
+//! taken out of its room and added to the [`Inventory`] when the player interacts with it.
+
+mod manifest;
+
+use bevy::prelude::*;
+use leafwing_manifest::{identifier::Id, manifest::Manifest};
+
+use crate::{
+    map::{OnInteract, Prop, TakeProp},
+    screens::Screen,
+};
+
+pub use manifest::{ItemDefinition, ItemManifest};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(manifest::plugin);
+    app.add_event::<InventoryChanged>();
+    app.add_event::<UseItem>();
+    app.init_resource::<Inventory>();
+    app.add_systems(OnEnter(Screen::Gameplay), |mut commands: Commands| {
+        commands.insert_resource(Inventory::default());
+    });
+    app.add_systems(
+        Update,
+        (
+            send_inventory_changes.run_if(resource_changed::<Inventory>),
+            log_inventory_changes.run_if(on_event::<InventoryChanged>()),
+        )
+            .chain(),
+    );
+    app.observe(pick_up_items);
+}
+
+/// The interaction of props that are picked up as an item, followed by the item's name
+const PICK_UP_INTERACTION: &str = "pick-up:";
+
+/// The items the player is carrying, in the order they were first picked up
+#[derive(Resource, Debug, Default)]
+pub struct Inventory {
+    items: Vec<(Id<ItemDefinition>, u32)>,
+    /// Changes that haven't been sent as [`InventoryChanged`] events yet
+    changes: Vec<InventoryChanged>,
+}
+
+impl Inventory {
+    /// Add some of an item
+    pub fn add(&mut self, item: Id<ItemDefinition>, count: u32) {
+        if count == 0 {
+            return;
+        }
+        let held = match self.items.iter_mut().find(|(id, _)| *id == item) {
+            Some((_, held)) => {
+                *held += count;
+                *held
+            }
+            None => {
+                self.items.push((item, count));
+                count
+            }
+        };
+        self.changes.push(InventoryChanged { item, count: held });
+    }
+
+    /// Remove some of an item. Returns `false` and leaves the inventory as it is if there isn't
+    /// enough of the item.
+    pub fn remove(&mut self, item: Id<ItemDefinition>, count: u32) -> bool {
+        let Some(index) = self.items.iter().position(|(id, _)| *id == item) else {
+            return false;
+        };
+        let held = &mut self.items[index].1;
+        if *held < count {
+            return false;
+        }
+        *held -= count;
+        let held = *held;
+        if held == 0 {
+            self.items.remove(index);
+        }
+        if count > 0 {
+            self.changes.push(InventoryChanged { item, count: held });
+        }
+        true
+    }
+
+    /// Whether the player is carrying any of an item
+    pub fn has(&self, item: Id<ItemDefinition>) -> bool {
+        self.count(item) > 0
+    }
+
+    /// How many of an item the player is carrying
+    pub fn count(&self, item: Id<ItemDefinition>) -> u32 {
+        self.items
+            .iter()
+            .find(|(id, _)| *id == item)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Every item being carried along with how many of it, in the order they were first picked up
+    pub fn items(&self) -> impl Iterator<Item = (Id<ItemDefinition>, u32)> + '_ {
+        self.items.iter().copied()
+    }
+}
+
+/// Sent when the amount of an item in the [`Inventory`] changes
+#[derive(Event, Debug, Clone, Copy)]
+pub struct InventoryChanged {
+    pub item: Id<ItemDefinition>,
+    /// How many of the item the player is carrying now
+    pub count: u32,
+}
+
+/// Asks to use up one of an item the player is carrying
+#[derive(Event, Debug)]
+pub struct UseItem {
+    pub item: Id<ItemDefinition>,
+}
+
+fn send_inventory_changes(
+    mut inventory: ResMut<Inventory>,
+    mut change_writer: EventWriter<InventoryChanged>,
+) {
+    // Draining the changes shouldn't count as changing the inventory.
+    change_writer.send_batch(inventory.bypass_change_detection().changes.drain(..));
+}
+
+fn log_inventory_changes(
+    mut events: EventReader<InventoryChanged>,
+    item_manifest: Res<ItemManifest>,
+) {
+    for event in events.read() {
+        let name = item_manifest
+            .get(event.item)
+            .map_or("an unknown item", |item| item.display_name.as_str());
+        debug!("Now carrying {} of {name}", event.count);
+    }
+}
+
+/// Pick up the item a prop stands for when it is interacted with
+fn pick_up_items(
+    trigger: Trigger<OnInteract>,
+    mut commands: Commands,
+    props: Query<&Prop>,
+    item_manifest: Res<ItemManifest>,
+    mut inventory: ResMut<Inventory>,
+) {
+    let Ok(prop) = props.get(trigger.entity()) else {
+        return;
+    };
+    let Some(item_name) = prop.interaction.strip_prefix(PICK_UP_INTERACTION) else {
+        return;
+    };
+    let item = Id::from_name(item_name);
+    if item_manifest.get(item).is_none() {
+        warn!("Tried to pick up an item that doesn't exist: {item_name}");
+        return;
+    }
+    inventory.add(item, 1);
+    commands.add(TakeProp(trigger.entity()));
+}
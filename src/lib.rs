@@ -2,9 +2,16 @@ pub mod audio;
 mod camera;
 #[cfg(feature = "dev")]
 mod dev_tools;
+mod input;
+mod inventory;
 mod map;
+mod monster;
+mod sanity;
+mod save;
 mod screens;
+mod settings;
 mod theme;
+mod turns;
 
 use bevy::{
     asset::AssetMetaCheck,
@@ -69,7 +76,19 @@ impl Plugin for AppPlugin {
             );
 
         // Add other plugins.
-        app.add_plugins((camera::plugin, screens::plugin, theme::plugin, map::plugin));
+        app.add_plugins((
+            camera::plugin,
+            screens::plugin,
+            theme::plugin,
+            map::plugin,
+            inventory::plugin,
+            monster::plugin,
+            sanity::plugin,
+            turns::plugin,
+            save::plugin,
+            input::plugin,
+            settings::plugin,
+        ));
 
         // Enable dev tools for dev builds.
         #[cfg(feature = "dev")]
@@ -0,0 +1,328 @@
+//! The house slowly corrupts the rooms in it.
+//!
+//! The room the player is in corrupts the longer they stay, and every room corrupts a little with
+//! each turn they take. Each time a room's corruption reaches a new level a
+//! [`CorruptionLevelChanged`] is sent: the room darkens and shows the animation its definition lists
+//! for that level in `corruption_animations`, and at the highest level it rearranges its doors.
+
+use bevy::{ecs::event::ManualEventReader, prelude::*, utils::HashSet};
+use leafwing_manifest::manifest::Manifest;
+use rand::seq::SliceRandom;
+
+use crate::{
+    screens::Screen,
+    turns::{turn_ended, TurnEnded},
+    AppSet,
+};
+
+use super::{
+    doors::{HeldKeys, SealDoor, UnsealDoor},
+    house_graph::HouseGraph,
+    house_shift::reachable_rooms,
+    load_house::current_house_layout,
+    manifest::RoomDefinitionManifest,
+    map_navigation::{FocusedRoom, MapRoomIndex},
+    room_animation::{RoomAnimation, SwitchRoomAnimation},
+    DoorKind, HouseLayout, KeyId, Room, RoomDefinition, RoomExit, RoomId,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Corruption>();
+    app.add_event::<CorruptionLevelChanged>();
+    app.add_systems(
+        Update,
+        (
+            corrupt_focused_room,
+            corrupt_every_room.run_if(turn_ended),
+            update_corruption_levels,
+            (show_corruption, shift_corrupted_rooms).run_if(on_event::<CorruptionLevelChanged>()),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// The highest level of corruption a room can reach
+pub const MAX_CORRUPTION_LEVEL: u32 = 3;
+/// How much the room the player is in corrupts every second
+const CORRUPTION_PER_SECOND: f32 = 0.02;
+/// How much every room corrupts each time a turn ends
+const CORRUPTION_PER_TURN: f32 = 0.05;
+
+/// How corrupted a room is. Every whole point of corruption is a level, up to
+/// [`MAX_CORRUPTION_LEVEL`].
+#[derive(Component, Reflect, Debug, Default, Clone, Copy)]
+#[reflect(Component)]
+pub struct Corruption {
+    amount: f32,
+    /// The level last announced with a [`CorruptionLevelChanged`]
+    level: u32,
+}
+
+impl Corruption {
+    pub fn new(amount: f32) -> Self {
+        let mut corruption = Corruption { amount, level: 0 };
+        corruption.level = corruption.current_level();
+        corruption
+    }
+
+    pub fn amount(&self) -> f32 {
+        self.amount
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn add(&mut self, amount: f32) {
+        self.amount = (self.amount + amount).min(MAX_CORRUPTION_LEVEL as f32);
+    }
+
+    fn current_level(&self) -> u32 {
+        (self.amount.max(0.0) as u32).min(MAX_CORRUPTION_LEVEL)
+    }
+}
+
+/// Sent when a room's corruption reaches a new level
+#[derive(Event, Debug)]
+pub struct CorruptionLevelChanged {
+    pub room_id: RoomId,
+    pub level: u32,
+}
+
+/// The animation a room shows at a level of corruption. Levels past the end of the room's
+/// `corruption_animations` keep showing the last one.
+pub fn corruption_animation(definition: &RoomDefinition, level: u32) -> &str {
+    match level.checked_sub(1) {
+        None => RoomAnimation::IDLE,
+        Some(index) => definition
+            .corruption_animations
+            .get(index as usize)
+            .or(definition.corruption_animations.last())
+            .map_or(RoomAnimation::IDLE, String::as_str),
+    }
+}
+
+/// The tint of a room at a level of corruption
+pub fn corruption_color(level: u32) -> Color {
+    let decay = level as f32 / MAX_CORRUPTION_LEVEL as f32;
+    Color::srgb(1.0 - 0.35 * decay, 1.0 - 0.5 * decay, 1.0 - 0.3 * decay)
+}
+
+fn corrupt_focused_room(time: Res<Time>, mut rooms: Query<&mut Corruption, With<FocusedRoom>>) {
+    for mut corruption in rooms.iter_mut() {
+        corruption.add(CORRUPTION_PER_SECOND * time.delta_seconds());
+    }
+}
+
+fn corrupt_every_room(mut events: EventReader<TurnEnded>, mut rooms: Query<&mut Corruption>) {
+    let turns = events.read().count();
+    for mut corruption in rooms.iter_mut() {
+        corruption.add(CORRUPTION_PER_TURN * turns as f32);
+    }
+}
+
+fn update_corruption_levels(
+    mut rooms: Query<(&RoomId, &mut Corruption), Changed<Corruption>>,
+    mut level_writer: EventWriter<CorruptionLevelChanged>,
+) {
+    for (room_id, mut corruption) in rooms.iter_mut() {
+        let level = corruption.current_level();
+        if level != corruption.level {
+            corruption.level = level;
+            level_writer.send(CorruptionLevelChanged {
+                room_id: *room_id,
+                level,
+            });
+        }
+    }
+}
+
+/// Darken corrupted rooms and switch them to the animation for their level
+fn show_corruption(
+    mut events: EventReader<CorruptionLevelChanged>,
+    mut animation_writer: EventWriter<SwitchRoomAnimation>,
+    house_graph: Res<HouseGraph>,
+    room_assets: Res<RoomDefinitionManifest>,
+    mut rooms: Query<(&Room, &mut Sprite)>,
+) {
+    for event in events.read() {
+        let Some((room, mut sprite)) = house_graph
+            .entity(event.room_id)
+            .and_then(|entity| rooms.get_mut(entity).ok())
+        else {
+            continue;
+        };
+        sprite.color = corruption_color(event.level);
+        if let Some(definition) = room_assets.get(room.room_def_id) {
+            animation_writer.send(SwitchRoomAnimation {
+                room_id: event.room_id,
+                animation: corruption_animation(definition, event.level).to_string(),
+            });
+        }
+    }
+}
+
+/// A room that becomes fully corrupted opens one of its sealed doors, or if it has none, seals one
+/// of its open doors that the house can do without
+fn shift_corrupted_rooms(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<CorruptionLevelChanged>>,
+) {
+    let corrupted: Vec<RoomId> = reader
+        .read(world.resource::<Events<CorruptionLevelChanged>>())
+        .filter(|event| event.level >= MAX_CORRUPTION_LEVEL)
+        .map(|event| event.room_id)
+        .collect();
+    if corrupted.is_empty() {
+        return;
+    }
+    let Some(mut layout) = current_house_layout(world) else {
+        return;
+    };
+    let player_room = world.resource::<MapRoomIndex>().0;
+    let held_keys = world.resource::<HeldKeys>().0.clone();
+    let house_graph = world.resource::<HouseGraph>();
+
+    let mut rng = rand::thread_rng();
+    let mut seals = Vec::new();
+    let mut unseals = Vec::new();
+    for room_id in corrupted {
+        let connections: Vec<_> = house_graph.connections(room_id).collect();
+
+        let sealed: Vec<_> = connections
+            .iter()
+            .filter(|(_, connection)| connection.door == DoorKind::Sealed)
+            .collect();
+        if let Some((exit, _)) = sealed.choose(&mut rng) {
+            unseals.push(UnsealDoor {
+                room_id,
+                exit: exit.clone(),
+            });
+            continue;
+        }
+
+        // Only seal doors that don't cut part of the house off, or keep the player from any room
+        // they can get to now.
+        let can_pass = |door: &DoorKind| *door != DoorKind::Sealed;
+        let sealable: Vec<_> = connections
+            .iter()
+            .filter(|(exit, connection)| {
+                connection.door == DoorKind::Open
+                    && house_graph.has_way_around(room_id, exit, can_pass)
+                    && !sealing_strands_player(&layout, room_id, exit, player_room, &held_keys)
+            })
+            .collect();
+        if let Some((exit, _)) = sealable.choose(&mut rng) {
+            layout.set_door(room_id, exit, DoorKind::Sealed);
+            seals.push(SealDoor {
+                room_id,
+                exit: exit.clone(),
+            });
+        }
+    }
+    world.send_event_batch(unseals);
+    world.send_event_batch(seals);
+}
+
+/// Whether sealing the door leading out of a room through the given exit would keep the player, in
+/// `player_room` and holding `held_keys`, from getting to a room they can get to now
+fn sealing_strands_player(
+    layout: &HouseLayout,
+    room_id: RoomId,
+    exit: &RoomExit,
+    player_room: RoomId,
+    held_keys: &HashSet<KeyId>,
+) -> bool {
+    let mut sealed = layout.clone();
+    sealed.set_door(room_id, exit, DoorKind::Sealed);
+    !reachable_rooms(layout, player_room, held_keys).is_subset(&reachable_rooms(
+        &sealed,
+        player_room,
+        held_keys,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::RoomConnectionDirection;
+
+    /// An entrance with two doors into the same room: an open one and one locked with the key lying
+    /// in that room
+    fn key_behind_a_locked_door() -> HouseLayout {
+        let json = r#"{
+            "rooms": [
+                {
+                    "room_id": 0,
+                    "position": [0, 0, 0],
+                    "footprint": [[0, 0], [1, 0]],
+                    "room_name": "wide-hall",
+                    "connections": [
+                        {
+                            "exit": { "cell": [0, 0], "direction": "North" },
+                            "room_id": 1,
+                            "other_exit": { "cell": [0, 0], "direction": "South" },
+                            "door": "Open"
+                        },
+                        {
+                            "exit": { "cell": [1, 0], "direction": "North" },
+                            "room_id": 1,
+                            "other_exit": { "cell": [1, 0], "direction": "South" },
+                            "door": { "Locked": 0 }
+                        }
+                    ]
+                },
+                {
+                    "room_id": 1,
+                    "position": [0, 1, 0],
+                    "footprint": [[0, 0], [1, 0]],
+                    "room_name": "wide-hall",
+                    "connections": [
+                        {
+                            "exit": { "cell": [0, 0], "direction": "South" },
+                            "room_id": 0,
+                            "other_exit": { "cell": [0, 0], "direction": "North" },
+                            "door": "Open"
+                        },
+                        {
+                            "exit": { "cell": [1, 0], "direction": "South" },
+                            "room_id": 0,
+                            "other_exit": { "cell": [1, 0], "direction": "North" },
+                            "door": { "Locked": 0 }
+                        }
+                    ],
+                    "keys": [0]
+                }
+            ]
+        }"#;
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn locked_doors_are_no_way_around_without_their_key() {
+        let layout = key_behind_a_locked_door();
+        let open_door = RoomExit {
+            cell: IVec2::new(0, 0),
+            direction: RoomConnectionDirection::North,
+        };
+        assert!(sealing_strands_player(
+            &layout,
+            RoomId(0),
+            &open_door,
+            RoomId(0),
+            &HashSet::new()
+        ));
+
+        // Once the player has the key the locked door is as good as the open one.
+        let held_keys = HashSet::from([KeyId(0)]);
+        assert!(!sealing_strands_player(
+            &layout,
+            RoomId(0),
+            &open_door,
+            RoomId(0),
+            &held_keys
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+//! Locked and sealed doors, and the keys that open them.
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::screens::Screen;
+
+use super::{
+    house_graph::HouseGraph, room_transition::RoomEntered, DoorKind, KeyId, Room, RoomExit, RoomId,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<HeldKeys>();
+    app.add_systems(OnEnter(Screen::Gameplay), |mut commands: Commands| {
+        commands.insert_resource(HeldKeys::default());
+    });
+
+    app.add_systems(Update, pick_up_keys.run_if(on_event::<RoomEntered>()));
+    app.add_systems(Update, unseal_doors.run_if(on_event::<UnsealDoor>()));
+    app.add_systems(Update, seal_doors.run_if(on_event::<SealDoor>()));
+    app.add_systems(Update, log_blocked_doors.run_if(on_event::<DoorBlocked>()));
+    app.add_event::<DoorBlocked>();
+    app.add_event::<UnsealDoor>();
+    app.add_event::<SealDoor>();
+}
+
+/// The keys the player is carrying
+#[derive(Resource, Debug, Default)]
+pub struct HeldKeys(pub HashSet<KeyId>);
+
+impl HeldKeys {
+    /// Whether the player can walk through the given door
+    pub fn can_pass(&self, door: &DoorKind) -> bool {
+        match door {
+            DoorKind::Open => true,
+            DoorKind::Locked(key_id) => self.0.contains(key_id),
+            DoorKind::Sealed => false,
+        }
+    }
+}
+
+/// Sent when the player tries to walk through a door they can't get through
+#[derive(Event, Debug)]
+pub struct DoorBlocked {
+    /// The room the player is trying to leave
+    pub room_id: RoomId,
+    pub exit: RoomExit,
+    pub door: DoorKind,
+}
+
+/// Opens the sealed door leading out of a room through the given exit, along with the door on the
+/// other side of it
+#[derive(Event, Debug)]
+pub struct UnsealDoor {
+    pub room_id: RoomId,
+    pub exit: RoomExit,
+}
+
+/// Seals the open door leading out of a room through the given exit, along with the door on the
+/// other side of it
+#[derive(Event, Debug)]
+pub struct SealDoor {
+    pub room_id: RoomId,
+    pub exit: RoomExit,
+}
+
+fn pick_up_keys(
+    mut events: EventReader<RoomEntered>,
+    mut held_keys: ResMut<HeldKeys>,
+    house_graph: Res<HouseGraph>,
+    mut rooms: Query<&mut Room>,
+) {
+    for event in events.read() {
+        let Some(mut room) = house_graph
+            .entity(event.room_id)
+            .and_then(|entity| rooms.get_mut(entity).ok())
+        else {
+            continue;
+        };
+        if room.keys.is_empty() {
+            continue;
+        }
+        held_keys.0.extend(room.keys.drain(..));
+    }
+}
+
+fn log_blocked_doors(mut events: EventReader<DoorBlocked>) {
+    for event in events.read() {
+        info!(
+            "Room {:?} can't be left through {:?}, the door is {:?}",
+            event.room_id, event.exit, event.door
+        );
+    }
+}
+
+fn unseal_doors(
+    mut events: EventReader<UnsealDoor>,
+    house_graph: Res<HouseGraph>,
+    mut rooms: Query<&mut Room>,
+) {
+    for event in events.read() {
+        let Some((other_room_id, other_exit)) = house_graph
+            .entity(event.room_id)
+            .and_then(|entity| rooms.get_mut(entity).ok())
+            .and_then(|mut room| {
+                let connection = room
+                    .connections
+                    .get_mut(&event.exit)
+                    .filter(|connection| connection.door == DoorKind::Sealed)?;
+                connection.door = DoorKind::Open;
+                Some((connection.room_id, connection.other_exit.clone()))
+            })
+        else {
+            continue;
+        };
+
+        let Some(mut other_room) = house_graph
+            .entity(other_room_id)
+            .and_then(|entity| rooms.get_mut(entity).ok())
+        else {
+            continue;
+        };
+        if let Some(connection) = other_room.connections.get_mut(&other_exit) {
+            connection.door = DoorKind::Open;
+        }
+    }
+}
+
+fn seal_doors(
+    mut events: EventReader<SealDoor>,
+    house_graph: Res<HouseGraph>,
+    mut rooms: Query<&mut Room>,
+) {
+    for event in events.read() {
+        let Some((other_room_id, other_exit)) = house_graph
+            .entity(event.room_id)
+            .and_then(|entity| rooms.get_mut(entity).ok())
+            .and_then(|mut room| {
+                let connection = room
+                    .connections
+                    .get_mut(&event.exit)
+                    .filter(|connection| connection.door == DoorKind::Open)?;
+                connection.door = DoorKind::Sealed;
+                Some((connection.room_id, connection.other_exit.clone()))
+            })
+        else {
+            continue;
+        };
+
+        let Some(mut other_room) = house_graph
+            .entity(other_room_id)
+            .and_then(|entity| rooms.get_mut(entity).ok())
+        else {
+            continue;
+        };
+        if let Some(connection) = other_room.connections.get_mut(&other_exit) {
+            connection.door = DoorKind::Sealed;
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Which rooms the player has visited, and which they have only seen through a doorway.
+//!
+//! [`ExploredRooms`] is the source of truth. The [`Visited`] and [`Discovered`] components are kept
+//! in step with it so that rooms can be queried by how explored they are.
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::screens::Screen;
+
+use super::{
+    house_graph::HouseGraph,
+    map_navigation::{ChangeRoom, MapRoomIndex},
+    House, Room, RoomId,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Visited>();
+    app.register_type::<Discovered>();
+    app.init_resource::<ExploredRooms>();
+    app.add_systems(OnEnter(Screen::Gameplay), |mut commands: Commands| {
+        commands.insert_resource(ExploredRooms::default());
+    });
+
+    app.add_systems(
+        Update,
+        (
+            explore_rooms.run_if(on_event::<ChangeRoom>().or_else(house_spawned)),
+            update_exploration_components.run_if(resource_changed::<ExploredRooms>),
+        )
+            .chain(),
+    );
+}
+
+/// Marks a room the player has been in
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Visited;
+
+/// Marks a room the player has seen, either by being in it or by being in a room next to it
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Discovered;
+
+/// The rooms the player has visited and discovered. Every visited room is also discovered.
+#[derive(Resource, Debug, Default)]
+pub struct ExploredRooms {
+    visited: HashSet<RoomId>,
+    discovered: HashSet<RoomId>,
+}
+
+impl ExploredRooms {
+    /// Mark a room as visited, and the rooms it connects to as discovered
+    pub fn visit(&mut self, room_id: RoomId, room: &Room) {
+        self.visited.insert(room_id);
+        self.discovered.insert(room_id);
+        self.discovered.extend(
+            room.connections
+                .values()
+                .map(|connection| connection.room_id),
+        );
+    }
+
+    /// Mark a room as discovered without visiting it
+    pub fn discover(&mut self, room_id: RoomId) {
+        self.discovered.insert(room_id);
+    }
+
+    pub fn is_visited(&self, room_id: RoomId) -> bool {
+        self.visited.contains(&room_id)
+    }
+
+    pub fn is_discovered(&self, room_id: RoomId) -> bool {
+        self.discovered.contains(&room_id)
+    }
+
+    /// Every visited room, in order of their ids
+    pub fn visited(&self) -> Vec<RoomId> {
+        let mut visited: Vec<RoomId> = self.visited.iter().copied().collect();
+        visited.sort_by_key(|room_id| room_id.0);
+        visited
+    }
+
+    /// Every discovered room, visited or not, in order of their ids
+    pub fn discovered(&self) -> Vec<RoomId> {
+        let mut discovered: Vec<RoomId> = self.discovered.iter().copied().collect();
+        discovered.sort_by_key(|room_id| room_id.0);
+        discovered
+    }
+}
+
+fn house_spawned(houses: Query<(), Added<House>>) -> bool {
+    !houses.is_empty()
+}
+
+/// Visit the rooms being changed to, along with the room the player starts off in when a house is
+/// spawned
+fn explore_rooms(
+    mut events: EventReader<ChangeRoom>,
+    new_houses: Query<(), Added<House>>,
+    room_index: Res<MapRoomIndex>,
+    mut explored_rooms: ResMut<ExploredRooms>,
+    house_graph: Res<HouseGraph>,
+    rooms: Query<&Room>,
+) {
+    let start = (!new_houses.is_empty()).then_some(room_index.0);
+    for room_id in start
+        .into_iter()
+        .chain(events.read().map(|event| event.new_room_id))
+    {
+        if let Some(room) = house_graph
+            .entity(room_id)
+            .and_then(|entity| rooms.get(entity).ok())
+        {
+            explored_rooms.visit(room_id, room);
+        }
+    }
+}
+
+fn update_exploration_components(
+    mut commands: Commands,
+    explored_rooms: Res<ExploredRooms>,
+    rooms: Query<(Entity, &RoomId, Has<Visited>, Has<Discovered>), With<Room>>,
+) {
+    for (entity, room_id, has_visited, has_discovered) in rooms.iter() {
+        let mut entity = commands.entity(entity);
+        match (explored_rooms.is_visited(*room_id), has_visited) {
+            (true, false) => {
+                entity.insert(Visited);
+            }
+            (false, true) => {
+                entity.remove::<Visited>();
+            }
+            _ => {}
+        }
+        match (explored_rooms.is_discovered(*room_id), has_discovered) {
+            (true, false) => {
+                entity.insert(Discovered);
+            }
+            (false, true) => {
+                entity.remove::<Discovered>();
+            }
+            _ => {}
+        }
+    }
+}
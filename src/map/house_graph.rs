@@ -0,0 +1,488 @@
+//! The rooms of the spawned house as a graph, for looking rooms up and finding ways between them.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use super::{DoorKind, House, Room, RoomConnection, RoomExit, RoomId};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<HouseGraph>();
+    app.add_systems(Update, update_house_graph.run_if(rooms_changed));
+}
+
+/// How the rooms of the spawned house connect to each other.
+///
+/// It is built when the house is spawned and kept up to date as doors are opened.
+#[derive(Resource, Debug, Default)]
+pub struct HouseGraph {
+    rooms: HashMap<RoomId, GraphRoom>,
+    /// How many rooms away from the entrance each room is, going through any door
+    entrance_distances: HashMap<RoomId, u32>,
+}
+
+#[derive(Debug)]
+struct GraphRoom {
+    entity: Entity,
+    /// The room's connections, ordered by exit so that searches always go the same way
+    connections: Vec<(RoomExit, RoomConnection)>,
+}
+
+impl HouseGraph {
+    /// Build the graph from every room's id, entity and connections
+    pub fn new<'a>(
+        rooms: impl IntoIterator<Item = (RoomId, Entity, &'a HashMap<RoomExit, RoomConnection>)>,
+    ) -> Self {
+        let rooms: HashMap<RoomId, GraphRoom> = rooms
+            .into_iter()
+            .map(|(room_id, entity, connections)| {
+                let mut connections: Vec<(RoomExit, RoomConnection)> = connections
+                    .iter()
+                    .map(|(exit, connection)| (exit.clone(), connection.clone()))
+                    .collect();
+                connections.sort_by(|(a, _), (b, _)| {
+                    (a.direction.clone(), a.cell.x, a.cell.y).cmp(&(
+                        b.direction.clone(),
+                        b.cell.x,
+                        b.cell.y,
+                    ))
+                });
+                (
+                    room_id,
+                    GraphRoom {
+                        entity,
+                        connections,
+                    },
+                )
+            })
+            .collect();
+
+        let mut graph = HouseGraph {
+            rooms,
+            entrance_distances: HashMap::new(),
+        };
+        graph.entrance_distances = graph.distances_from(RoomId(0), |_| true);
+        graph
+    }
+
+    /// The entity of a room
+    pub fn entity(&self, room_id: RoomId) -> Option<Entity> {
+        self.rooms.get(&room_id).map(|room| room.entity)
+    }
+
+    /// Whether the room is part of the house
+    pub fn contains(&self, room_id: RoomId) -> bool {
+        self.rooms.contains_key(&room_id)
+    }
+
+    /// Every connection leading out of a room
+    pub fn connections(
+        &self,
+        room_id: RoomId,
+    ) -> impl Iterator<Item = &(RoomExit, RoomConnection)> {
+        self.rooms
+            .get(&room_id)
+            .into_iter()
+            .flat_map(|room| room.connections.iter())
+    }
+
+    /// The rooms a room has a door to, each listed once
+    pub fn neighbours(&self, room_id: RoomId) -> Vec<RoomId> {
+        let mut neighbours: Vec<RoomId> = Vec::new();
+        for (_, connection) in self.connections(room_id) {
+            if !neighbours.contains(&connection.room_id) {
+                neighbours.push(connection.room_id);
+            }
+        }
+        neighbours
+    }
+
+    /// How many rooms away from the entrance a room is, ignoring whether doors are locked, or `None`
+    /// if it can't be reached at all
+    pub fn distance_from_entrance(&self, room_id: RoomId) -> Option<u32> {
+        self.entrance_distances.get(&room_id).copied()
+    }
+
+    /// Whether a room only leads to one other room
+    pub fn is_dead_end(&self, room_id: RoomId) -> bool {
+        self.neighbours(room_id).len() == 1
+    }
+
+    /// Every room that only leads to one other room, ordered by id
+    pub fn dead_ends(&self) -> Vec<RoomId> {
+        let mut dead_ends: Vec<RoomId> = self
+            .rooms
+            .keys()
+            .copied()
+            .filter(|room_id| self.is_dead_end(*room_id))
+            .collect();
+        dead_ends.sort_by_key(|room_id| room_id.0);
+        dead_ends
+    }
+
+    /// The fewest rooms to go through to get from `from` to `to`, including both, using only doors
+    /// that `can_pass` allows. `None` if there is no way there.
+    pub fn shortest_path(
+        &self,
+        from: RoomId,
+        to: RoomId,
+        can_pass: impl Fn(&DoorKind) -> bool,
+    ) -> Option<Vec<RoomId>> {
+        if !self.contains(from) || !self.contains(to) {
+            return None;
+        }
+
+        let mut came_from: HashMap<RoomId, RoomId> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        let mut seen = HashSet::from([from]);
+        while let Some(room_id) = queue.pop_front() {
+            if room_id == to {
+                let mut path = vec![to];
+                let mut current = to;
+                while let Some(previous) = came_from.get(&current) {
+                    path.push(*previous);
+                    current = *previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for (_, connection) in self.connections(room_id) {
+                if can_pass(&connection.door) && seen.insert(connection.room_id) {
+                    came_from.insert(connection.room_id, room_id);
+                    queue.push_back(connection.room_id);
+                }
+            }
+        }
+        None
+    }
+
+    /// Every room that can be got to from `from`, including itself, using only doors that
+    /// `can_pass` allows
+    pub fn reachable_from(
+        &self,
+        from: RoomId,
+        can_pass: impl Fn(&DoorKind) -> bool,
+    ) -> HashSet<RoomId> {
+        self.distances_from(from, can_pass).into_keys().collect()
+    }
+
+    /// Whether `to` can be got to from `from` using only doors that `can_pass` allows
+    pub fn is_reachable(
+        &self,
+        from: RoomId,
+        to: RoomId,
+        can_pass: impl Fn(&DoorKind) -> bool,
+    ) -> bool {
+        self.shortest_path(from, to, can_pass).is_some()
+    }
+
+    /// Whether the rooms on either side of a room's exit could still get to each other, using only
+    /// doors that `can_pass` allows, if the door through that exit were closed
+    pub fn has_way_around(
+        &self,
+        room_id: RoomId,
+        exit: &RoomExit,
+        can_pass: impl Fn(&DoorKind) -> bool,
+    ) -> bool {
+        let Some((_, closed)) = self
+            .connections(room_id)
+            .find(|(room_exit, _)| room_exit == exit)
+        else {
+            return false;
+        };
+
+        let mut queue = VecDeque::from([room_id]);
+        let mut seen = HashSet::from([room_id]);
+        while let Some(current) = queue.pop_front() {
+            if current == closed.room_id {
+                return true;
+            }
+            for (current_exit, connection) in self.connections(current) {
+                let is_closed = current == room_id && current_exit == exit;
+                if !is_closed && can_pass(&connection.door) && seen.insert(connection.room_id) {
+                    queue.push_back(connection.room_id);
+                }
+            }
+        }
+        false
+    }
+
+    /// How many rooms away from `from` every room it can get to is
+    fn distances_from(
+        &self,
+        from: RoomId,
+        can_pass: impl Fn(&DoorKind) -> bool,
+    ) -> HashMap<RoomId, u32> {
+        let mut distances = HashMap::new();
+        if !self.contains(from) {
+            return distances;
+        }
+
+        distances.insert(from, 0);
+        let mut queue = VecDeque::from([from]);
+        while let Some(room_id) = queue.pop_front() {
+            let distance = distances[&room_id];
+            for (_, connection) in self.connections(room_id) {
+                if can_pass(&connection.door) && !distances.contains_key(&connection.room_id) {
+                    distances.insert(connection.room_id, distance + 1);
+                    queue.push_back(connection.room_id);
+                }
+            }
+        }
+        distances
+    }
+}
+
+/// Build the [`HouseGraph`] for the spawned house
+pub(super) fn rebuild_house_graph(world: &mut World) {
+    let mut houses = world.query::<&House>();
+    let Ok(house) = houses.get_single(world) else {
+        world.insert_resource(HouseGraph::default());
+        return;
+    };
+    let room_entities: Vec<(RoomId, Entity)> = house
+        .rooms
+        .iter()
+        .map(|(room_id, entity)| (*room_id, *entity))
+        .collect();
+
+    let mut rooms = world.query::<&Room>();
+    let graph = HouseGraph::new(room_entities.into_iter().filter_map(|(room_id, entity)| {
+        let room = rooms.get(world, entity).ok()?;
+        Some((room_id, entity, &room.connections))
+    }));
+    world.insert_resource(graph);
+}
+
+fn rooms_changed(rooms: Query<(), Changed<Room>>) -> bool {
+    !rooms.is_empty()
+}
+
+fn update_house_graph(world: &mut World) {
+    rebuild_house_graph(world);
+}
+
+#[cfg(test)]
+impl HouseGraph {
+    /// A house where each `(from, to, door)` is a door between two rooms. Every door gets its own
+    /// exit so that rooms can have several doors to each other.
+    pub fn from_doors(room_count: u32, doors: &[(u32, u32, DoorKind)]) -> Self {
+        use super::RoomConnectionDirection;
+        let mut connections: Vec<HashMap<RoomExit, RoomConnection>> =
+            (0..room_count).map(|_| HashMap::new()).collect();
+        for (index, (from, to, door)) in doors.iter().enumerate() {
+            let from_exit = RoomExit {
+                cell: IVec2::new(index as i32, 0),
+                direction: RoomConnectionDirection::East,
+            };
+            let to_exit = RoomExit {
+                cell: IVec2::new(index as i32, 0),
+                direction: RoomConnectionDirection::West,
+            };
+            connections[*from as usize].insert(
+                from_exit.clone(),
+                RoomConnection {
+                    room_id: RoomId(*to),
+                    other_exit: to_exit.clone(),
+                    door: *door,
+                },
+            );
+            connections[*to as usize].insert(
+                to_exit,
+                RoomConnection {
+                    room_id: RoomId(*from),
+                    other_exit: from_exit,
+                    door: *door,
+                },
+            );
+        }
+
+        HouseGraph::new(connections.iter().enumerate().map(|(index, connections)| {
+            (
+                RoomId(index as u32),
+                Entity::from_raw(index as u32),
+                connections,
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::KeyId;
+
+    fn ids(ids: &[u32]) -> Vec<RoomId> {
+        ids.iter().map(|id| RoomId(*id)).collect()
+    }
+
+    fn any_door(_door: &DoorKind) -> bool {
+        true
+    }
+
+    fn open_doors(door: &DoorKind) -> bool {
+        *door == DoorKind::Open
+    }
+
+    /// 0 - 1 - 2 - 3
+    ///     |       |
+    ///     4 ----- 5   6
+    fn looped_house() -> HouseGraph {
+        HouseGraph::from_doors(
+            7,
+            &[
+                (0, 1, DoorKind::Open),
+                (1, 2, DoorKind::Open),
+                (2, 3, DoorKind::Open),
+                (1, 4, DoorKind::Open),
+                (4, 5, DoorKind::Open),
+                (3, 5, DoorKind::Locked(KeyId(0))),
+            ],
+        )
+    }
+
+    #[test]
+    fn looks_up_entities() {
+        let graph = looped_house();
+        assert_eq!(graph.entity(RoomId(3)), Some(Entity::from_raw(3)));
+        assert_eq!(graph.entity(RoomId(7)), None);
+        assert!(graph.contains(RoomId(6)));
+    }
+
+    #[test]
+    fn shortest_path_takes_the_fewest_rooms() {
+        let graph = looped_house();
+        assert_eq!(
+            graph.shortest_path(RoomId(0), RoomId(5), any_door),
+            Some(ids(&[0, 1, 4, 5]))
+        );
+        assert_eq!(
+            graph.shortest_path(RoomId(3), RoomId(5), any_door),
+            Some(ids(&[3, 5]))
+        );
+        assert_eq!(
+            graph.shortest_path(RoomId(2), RoomId(2), any_door),
+            Some(ids(&[2]))
+        );
+    }
+
+    #[test]
+    fn shortest_path_avoids_doors_that_cant_be_passed() {
+        let graph = looped_house();
+        assert_eq!(
+            graph.shortest_path(RoomId(3), RoomId(5), open_doors),
+            Some(ids(&[3, 2, 1, 4, 5]))
+        );
+    }
+
+    #[test]
+    fn no_path_to_unconnected_rooms() {
+        let graph = looped_house();
+        assert_eq!(graph.shortest_path(RoomId(0), RoomId(6), any_door), None);
+        assert_eq!(graph.shortest_path(RoomId(0), RoomId(9), any_door), None);
+        assert!(!graph.is_reachable(RoomId(6), RoomId(0), any_door));
+    }
+
+    #[test]
+    fn distance_from_entrance_ignores_locks() {
+        let graph = HouseGraph::from_doors(
+            3,
+            &[(0, 1, DoorKind::Sealed), (1, 2, DoorKind::Locked(KeyId(1)))],
+        );
+        assert_eq!(graph.distance_from_entrance(RoomId(0)), Some(0));
+        assert_eq!(graph.distance_from_entrance(RoomId(1)), Some(1));
+        assert_eq!(graph.distance_from_entrance(RoomId(2)), Some(2));
+
+        let graph = looped_house();
+        assert_eq!(graph.distance_from_entrance(RoomId(5)), Some(3));
+        assert_eq!(graph.distance_from_entrance(RoomId(6)), None);
+    }
+
+    #[test]
+    fn finds_dead_ends() {
+        // 0 - 1 - 2
+        //     |
+        //     3
+        let graph = HouseGraph::from_doors(
+            4,
+            &[
+                (0, 1, DoorKind::Open),
+                (1, 2, DoorKind::Open),
+                (1, 3, DoorKind::Open),
+            ],
+        );
+        assert_eq!(graph.dead_ends(), ids(&[0, 2, 3]));
+        assert!(!graph.is_dead_end(RoomId(1)));
+    }
+
+    #[test]
+    fn several_doors_to_the_same_room_are_still_a_dead_end() {
+        let graph = HouseGraph::from_doors(
+            3,
+            &[
+                (0, 1, DoorKind::Open),
+                (1, 2, DoorKind::Open),
+                (1, 2, DoorKind::Open),
+            ],
+        );
+        assert_eq!(graph.neighbours(RoomId(2)), ids(&[1]));
+        assert!(graph.is_dead_end(RoomId(2)));
+    }
+
+    #[test]
+    fn reachability_respects_doors() {
+        let graph = HouseGraph::from_doors(
+            4,
+            &[
+                (0, 1, DoorKind::Open),
+                (1, 2, DoorKind::Locked(KeyId(0))),
+                (2, 3, DoorKind::Sealed),
+            ],
+        );
+        let reachable = graph.reachable_from(RoomId(0), open_doors);
+        assert_eq!(reachable, HashSet::from_iter(ids(&[0, 1])));
+
+        let with_key = |door: &DoorKind| *door != DoorKind::Sealed;
+        let reachable = graph.reachable_from(RoomId(0), with_key);
+        assert_eq!(reachable, HashSet::from_iter(ids(&[0, 1, 2])));
+
+        assert!(graph.is_reachable(RoomId(0), RoomId(3), any_door));
+        assert!(!graph.is_reachable(RoomId(0), RoomId(3), with_key));
+    }
+
+    #[test]
+    fn empty_house_has_nothing() {
+        let graph = HouseGraph::default();
+        assert_eq!(graph.entity(RoomId(0)), None);
+        assert_eq!(graph.distance_from_entrance(RoomId(0)), None);
+        assert!(graph.dead_ends().is_empty());
+        assert!(graph.reachable_from(RoomId(0), any_door).is_empty());
+    }
+
+    #[test]
+    fn doors_in_a_loop_have_a_way_around() {
+        let graph = looped_house();
+        let exit = |room_id: u32, to: u32| {
+            graph
+                .connections(RoomId(room_id))
+                .find(|(_, connection)| connection.room_id == RoomId(to))
+                .map(|(exit, _)| exit.clone())
+                .unwrap()
+        };
+        assert!(graph.has_way_around(RoomId(1), &exit(1, 2), any_door));
+        assert!(graph.has_way_around(RoomId(5), &exit(5, 4), any_door));
+        assert!(!graph.has_way_around(RoomId(0), &exit(0, 1), any_door));
+        // The only other way from 2 to 3 is through the locked door.
+        assert!(!graph.has_way_around(RoomId(2), &exit(2, 3), open_doors));
+    }
+
+    #[test]
+    fn a_second_door_to_the_same_room_is_a_way_around() {
+        let graph = HouseGraph::from_doors(2, &[(0, 1, DoorKind::Open), (0, 1, DoorKind::Open)]);
+        let (exit, _) = graph.connections(RoomId(0)).next().unwrap();
+        assert!(graph.has_way_around(RoomId(0), exit, any_door));
+    }
+}
@@ -0,0 +1,1732 @@
+//! Lays out the rooms of a house without touching the ECS.
+//!
+//! [`generate`] only deals in plain data so that it can be run and tested without a [`World`](bevy::prelude::World).
+//! [`GenerateMap`](super::GenerateMap) then spawns the rooms from the resulting [`HouseLayout`].
+//!
+//! A [`HouseLayout`] can be written to and read from JSON, so hand-made houses can be spawned with
+//! [`LoadHouse`](super::LoadHouse) just like generated ones.
+
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    ops::Add,
+};
+
+use bevy::{
+    math::{IVec2, IVec3},
+    utils::{HashMap, HashSet},
+};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    DoorKind, KeyId, RoomConnection, RoomConnectionDirection, RoomDefinition, RoomExit, RoomId,
+};
+
+/// The parts of a [`RoomDefinition`] that matter when laying out a house.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomBlueprint {
+    pub room_name: String,
+    /// The cells the room covers, relative to its position.
+    pub footprint: Vec<IVec2>,
+    /// Where doors can go on the edges of the footprint.
+    pub exits: Vec<RoomExit>,
+    /// How likely this room is to be picked compared to the other rooms that fit.
+    pub weight: f32,
+    /// The fewest times this room has to appear in the house.
+    pub min_count: u32,
+    /// The most times this room may appear in the house.
+    pub max_count: Option<u32>,
+    /// The fewest steps away from the entrance this room may be.
+    pub min_depth: u32,
+    /// The most steps away from the entrance this room may be.
+    pub max_depth: Option<u32>,
+}
+
+impl RoomBlueprint {
+    fn allows_count(&self, count: u32) -> bool {
+        self.max_count.is_none_or(|max_count| count < max_count)
+    }
+
+    fn allows_depth(&self, depth: u32) -> bool {
+        depth >= self.min_depth && self.max_depth.is_none_or(|max_depth| depth <= max_depth)
+    }
+
+    /// The cells of the grid this room covers when placed at the given position.
+    fn cells(&self, position: IVec3) -> impl Iterator<Item = IVec3> + '_ {
+        self.footprint
+            .iter()
+            .map(move |cell| position + cell.extend(0))
+    }
+}
+
+impl From<&RoomDefinition> for RoomBlueprint {
+    fn from(definition: &RoomDefinition) -> Self {
+        RoomBlueprint {
+            room_name: definition.room_name.clone(),
+            footprint: definition.footprint.clone(),
+            exits: definition.exits.clone(),
+            weight: definition.weight,
+            min_count: definition.min_count,
+            max_count: definition.max_count,
+            min_depth: definition.min_depth,
+            max_depth: definition.max_depth,
+        }
+    }
+}
+
+/// Settings for [`generate`].
+#[derive(Debug, Clone)]
+pub struct LayoutParams {
+    /// How many rooms the house should have, including the entrance.
+    pub room_count: u32,
+    /// The name of the blueprint used for the first room.
+    pub entrance: String,
+    /// How many floors the house has above the ground floor.
+    pub floors_above: u8,
+    /// How many floors the house has below the ground floor.
+    pub floors_below: u8,
+    /// How many doors get locked, each with a key hidden somewhere in the house.
+    pub locked_doors: u8,
+    /// How many times generation starts over from just the entrance before giving up.
+    pub max_attempts: u32,
+}
+
+impl LayoutParams {
+    /// Whether a room may cover the given cell.
+    ///
+    /// Nothing is built south of the entrance and every room has to be on one of the house's floors.
+    fn contains(&self, position: IVec3) -> bool {
+        position.y >= 0
+            && position.z >= -(self.floors_below as i32)
+            && position.z <= self.floors_above as i32
+    }
+}
+
+impl Default for LayoutParams {
+    fn default() -> Self {
+        LayoutParams {
+            room_count: 10,
+            entrance: "entrance".to_string(),
+            floors_above: 0,
+            floors_below: 0,
+            locked_doors: 0,
+            max_attempts: 10,
+        }
+    }
+}
+
+/// Why a house could not be generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapGenerationError {
+    /// There are no room definitions to build the house out of.
+    EmptyManifest,
+    /// A room definition that generation relies on does not exist.
+    MissingDefinition(String),
+    /// A house layout can't be spawned as it is, for the given reason.
+    InvalidLayout(String),
+    /// A room definition's weight is negative or not a number, so it can't be picked by.
+    InvalidWeight(String),
+    /// A room definition has no texture for an animation every room needs, such as `idle`.
+    MissingTexture { room_name: String, texture: String },
+    /// The room definitions could not be fit together into a house with the requested number of
+    /// rooms within the attempt budget.
+    UnsatisfiableRoomCount {
+        requested: u32,
+        /// The most rooms any attempt managed to place.
+        placed: u32,
+    },
+    /// Houses of the requested size could be built, but none of them met the counts and depths
+    /// the room definitions ask for within the attempt budget.
+    UnsatisfiableConstraints,
+}
+
+impl Display for MapGenerationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MapGenerationError::EmptyManifest => write!(f, "there are no room definitions"),
+            MapGenerationError::MissingDefinition(room_name) => {
+                write!(f, "there is no room definition named `{room_name}`")
+            }
+            MapGenerationError::InvalidLayout(reason) => {
+                write!(f, "the house layout is invalid: {reason}")
+            }
+            MapGenerationError::InvalidWeight(room_name) => {
+                write!(f, "the room definition `{room_name}` has an invalid weight")
+            }
+            MapGenerationError::MissingTexture { room_name, texture } => {
+                write!(
+                    f,
+                    "the room definition `{room_name}` has no `{texture}` texture"
+                )
+            }
+            MapGenerationError::UnsatisfiableRoomCount { requested, placed } => write!(
+                f,
+                "could only place {placed} of the {requested} requested rooms"
+            ),
+            MapGenerationError::UnsatisfiableConstraints => {
+                write!(f, "could not meet the room count and depth constraints")
+            }
+        }
+    }
+}
+
+impl Error for MapGenerationError {}
+
+/// A single room placed in a [`HouseLayout`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutRoom {
+    pub room_id: RoomId,
+    /// Where the room is in the house. `z` is the floor the room is on, with the ground floor being
+    /// `0`.
+    pub position: IVec3,
+    /// The cells the room covers, relative to its position.
+    pub footprint: Vec<IVec2>,
+    /// The name of the [`RoomBlueprint`] this room was made from.
+    pub room_name: String,
+    #[serde(with = "connection_list")]
+    pub connections: HashMap<RoomExit, RoomConnection>,
+    /// The keys lying in this room.
+    #[serde(default)]
+    pub keys: Vec<KeyId>,
+    /// The props that have been taken out of this room, by their place in the room's definition.
+    #[serde(default)]
+    pub taken_props: Vec<u32>,
+    /// How corrupted the room is.
+    #[serde(default)]
+    pub corruption: f32,
+}
+
+/// JSON objects can only have strings as keys, so connections are stored as a list of exits and
+/// where they lead instead.
+mod connection_list {
+    use bevy::utils::HashMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{RoomConnection, RoomExit};
+
+    #[derive(Serialize, Deserialize)]
+    struct Entry {
+        exit: RoomExit,
+        #[serde(flatten)]
+        connection: RoomConnection,
+    }
+
+    pub fn serialize<S: Serializer>(
+        connections: &HashMap<RoomExit, RoomConnection>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<Entry> = connections
+            .iter()
+            .map(|(exit, connection)| Entry {
+                exit: exit.clone(),
+                connection: connection.clone(),
+            })
+            .collect();
+        // Keep the output the same every time the same house is written.
+        entries.sort_by_key(|entry| {
+            (
+                entry.exit.cell.x,
+                entry.exit.cell.y,
+                entry.exit.direction.clone(),
+            )
+        });
+        entries.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<RoomExit, RoomConnection>, D::Error> {
+        let entries = Vec::<Entry>::deserialize(deserializer)?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| (entry.exit, entry.connection))
+            .collect())
+    }
+}
+
+/// Every room of a house along with how they connect to each other.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct HouseLayout {
+    /// The seed the house was generated from. Hand-made houses don't have one.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// All the rooms in the house, indexed by their [`RoomId`].
+    pub rooms: Vec<LayoutRoom>,
+}
+
+impl HouseLayout {
+    /// Make sure the layout describes a house that can be spawned, such as after it was written by
+    /// hand. Every room's id has to match its place in [`HouseLayout::rooms`], every connection has to
+    /// lead to a room with a matching connection back, and every room's footprint has to match the
+    /// footprint of its definition, which `footprint` looks up by name.
+    pub fn check<'a>(
+        &self,
+        footprint: impl Fn(&str) -> Option<&'a [IVec2]>,
+    ) -> Result<(), MapGenerationError> {
+        let invalid = |reason: String| Err(MapGenerationError::InvalidLayout(reason));
+        if self.rooms.is_empty() {
+            return invalid("there are no rooms".to_string());
+        }
+        for (index, room) in self.rooms.iter().enumerate() {
+            if room.room_id.0 as usize != index {
+                return invalid(format!("room {index} has the id {}", room.room_id.0));
+            }
+            let Some(footprint) = footprint(&room.room_name) else {
+                return Err(MapGenerationError::MissingDefinition(
+                    room.room_name.clone(),
+                ));
+            };
+            if room.footprint != footprint {
+                return invalid(format!(
+                    "room {index} doesn't have the footprint of `{}`",
+                    room.room_name
+                ));
+            }
+        }
+        for room in &self.rooms {
+            for (exit, connection) in &room.connections {
+                let Some(other) = self.rooms.get(connection.room_id.0 as usize) else {
+                    return invalid(format!(
+                        "room {} connects to room {}, which doesn't exist",
+                        room.room_id.0, connection.room_id.0
+                    ));
+                };
+                let leads_back =
+                    other
+                        .connections
+                        .get(&connection.other_exit)
+                        .is_some_and(|back| {
+                            back.room_id == room.room_id
+                                && back.other_exit == *exit
+                                && back.door == connection.door
+                        });
+                if !leads_back {
+                    return invalid(format!(
+                        "room {} connects to room {}, which doesn't connect back the same way",
+                        room.room_id.0, connection.room_id.0
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// How many steps each room is from the entrance, indexed by [`RoomId`].
+    ///
+    /// Rooms that can't be reached from the entrance are `None`.
+    pub fn depths(&self) -> Vec<Option<u32>> {
+        let mut depths = vec![None; self.rooms.len()];
+        if self.rooms.is_empty() {
+            return depths;
+        }
+
+        depths[0] = Some(0);
+        let mut queue = VecDeque::from([RoomId(0)]);
+        while let Some(room_id) = queue.pop_front() {
+            let depth = depths[room_id.0 as usize].unwrap();
+            for connection in self.rooms[room_id.0 as usize].connections.values() {
+                if depths[connection.room_id.0 as usize].is_none() {
+                    depths[connection.room_id.0 as usize] = Some(depth + 1);
+                    queue.push_back(connection.room_id);
+                }
+            }
+        }
+        depths
+    }
+
+    /// Which rooms the player can get to from the entrance, indexed by [`RoomId`].
+    ///
+    /// The player picks up every key in the rooms they get to, and can go through any door they have
+    /// the key for. Sealed doors are never passable.
+    pub fn reachable_rooms(&self) -> Vec<bool> {
+        self.reachable_rooms_from(RoomId(0), &HashSet::new())
+    }
+
+    /// Which rooms the player can get to from the given room while already holding some keys,
+    /// indexed by [`RoomId`]. Works like [`HouseLayout::reachable_rooms`] otherwise.
+    pub fn reachable_rooms_from(&self, start: RoomId, held_keys: &HashSet<KeyId>) -> Vec<bool> {
+        let mut reachable = vec![false; self.rooms.len()];
+        if start.0 as usize >= self.rooms.len() {
+            return reachable;
+        }
+
+        let mut held_keys = held_keys.clone();
+        let mut stack = vec![start];
+        loop {
+            while let Some(room_id) = stack.pop() {
+                if reachable[room_id.0 as usize] {
+                    continue;
+                }
+                reachable[room_id.0 as usize] = true;
+
+                let room = &self.rooms[room_id.0 as usize];
+                held_keys.extend(room.keys.iter().copied());
+                for connection in room.connections.values() {
+                    if connection.door == DoorKind::Open {
+                        stack.push(connection.room_id);
+                    }
+                }
+            }
+
+            // Keys picked up along the way may open doors we walked past, so go back through those.
+            for (index, room) in self.rooms.iter().enumerate() {
+                if !reachable[index] {
+                    continue;
+                }
+                for connection in room.connections.values() {
+                    if let DoorKind::Locked(key_id) = connection.door {
+                        if held_keys.contains(&key_id) && !reachable[connection.room_id.0 as usize]
+                        {
+                            stack.push(connection.room_id);
+                        }
+                    }
+                }
+            }
+            if stack.is_empty() {
+                return reachable;
+            }
+        }
+    }
+
+    /// Whether the player can get to every room in the house.
+    pub fn is_completable(&self) -> bool {
+        self.reachable_rooms()
+            .into_iter()
+            .all(|reachable| reachable)
+    }
+
+    /// Whether two rooms could trade places, which needs them to be different rooms with the same
+    /// footprint and exits.
+    pub fn can_swap_rooms(&self, a: RoomId, b: RoomId) -> bool {
+        let (Some(room_a), Some(room_b)) =
+            (self.rooms.get(a.0 as usize), self.rooms.get(b.0 as usize))
+        else {
+            return false;
+        };
+        a != b
+            && room_a.footprint == room_b.footprint
+            && room_a.connections.len() == room_b.connections.len()
+            && room_a
+                .connections
+                .keys()
+                .all(|exit| room_b.connections.contains_key(exit))
+    }
+
+    /// Have two rooms trade places. Each takes the other's position and doors, while keeping what is
+    /// in it. Returns `false` and leaves the layout alone if [`HouseLayout::can_swap_rooms`] doesn't
+    /// allow it.
+    pub fn swap_rooms(&mut self, a: RoomId, b: RoomId) -> bool {
+        if !self.can_swap_rooms(a, b) {
+            return false;
+        }
+        let swap_id = |room_id: RoomId| match room_id {
+            room_id if room_id == a => b,
+            room_id if room_id == b => a,
+            room_id => room_id,
+        };
+
+        let position_a = self.rooms[a.0 as usize].position;
+        self.rooms[a.0 as usize].position = self.rooms[b.0 as usize].position;
+        self.rooms[b.0 as usize].position = position_a;
+        let connections_a = std::mem::take(&mut self.rooms[a.0 as usize].connections);
+        let connections_b = std::mem::take(&mut self.rooms[b.0 as usize].connections);
+        self.rooms[a.0 as usize].connections = connections_b;
+        self.rooms[b.0 as usize].connections = connections_a;
+
+        for room in &mut self.rooms {
+            for connection in room.connections.values_mut() {
+                connection.room_id = swap_id(connection.room_id);
+            }
+        }
+        true
+    }
+
+    /// Set the door leading out of a room through the given exit, on both sides.
+    pub fn set_door(&mut self, room_id: RoomId, exit: &RoomExit, door: DoorKind) {
+        let Some(connection) = self.rooms[room_id.0 as usize].connections.get_mut(exit) else {
+            return;
+        };
+        connection.door = door;
+        let other_room_id = connection.room_id;
+        let other_exit = connection.other_exit.clone();
+        if let Some(other_connection) = self.rooms[other_room_id.0 as usize]
+            .connections
+            .get_mut(&other_exit)
+        {
+            other_connection.door = door;
+        }
+    }
+}
+
+/// A room placed on the grid, before the rooms are connected to each other.
+struct PlacedRoom<'a> {
+    position: IVec3,
+    blueprint: &'a RoomBlueprint,
+}
+
+/// Every room placed so far, indexed by [`RoomId`], along with which room covers each cell.
+#[derive(Default)]
+struct PlacedRooms<'a> {
+    rooms: Vec<PlacedRoom<'a>>,
+    cells: HashMap<IVec3, RoomId>,
+}
+
+impl PlacedRooms<'_> {
+    /// Whether a room made from the blueprint fits at the given position without covering another
+    /// room or leaving the house.
+    fn fits(&self, params: &LayoutParams, position: IVec3, blueprint: &RoomBlueprint) -> bool {
+        blueprint
+            .cells(position)
+            .all(|cell| params.contains(cell) && !self.cells.contains_key(&cell))
+    }
+}
+
+/// Why a single attempt at generating the house failed.
+enum AttemptFailure {
+    /// Ran out of open doors after placing this many rooms.
+    OutOfDoors(usize),
+    /// All the rooms were placed but the result broke the blueprints' constraints.
+    BrokenConstraints,
+}
+
+/// Lay out a new house out of the given blueprints.
+///
+/// The same blueprints, params and rng state always produce the same layout. Generation gives up
+/// after [`LayoutParams::max_attempts`] so this always returns, even when the blueprints can never
+/// make a house of the requested size.
+pub fn generate(
+    blueprints: &[RoomBlueprint],
+    params: &LayoutParams,
+    rng: &mut impl Rng,
+) -> Result<HouseLayout, MapGenerationError> {
+    if blueprints.is_empty() {
+        return Err(MapGenerationError::EmptyManifest);
+    }
+    if let Some(blueprint) = blueprints
+        .iter()
+        .find(|blueprint| !(blueprint.weight >= 0.0 && blueprint.weight.is_finite()))
+    {
+        return Err(MapGenerationError::InvalidWeight(
+            blueprint.room_name.clone(),
+        ));
+    }
+
+    // Keep the blueprints in a stable order so that the layout does not depend on the order the
+    // caller happened to collect them in.
+    let mut blueprints: Vec<&RoomBlueprint> = blueprints.iter().collect();
+    blueprints.sort_by(|a, b| a.room_name.cmp(&b.room_name));
+
+    let entrance = *blueprints
+        .iter()
+        .find(|blueprint| blueprint.room_name == params.entrance)
+        .ok_or_else(|| MapGenerationError::MissingDefinition(params.entrance.clone()))?;
+    if !PlacedRooms::default().fits(params, IVec3::ZERO, entrance) {
+        return Err(MapGenerationError::UnsatisfiableConstraints);
+    }
+
+    // No amount of attempts will fit more required rooms than the house has space for.
+    let required_rooms: u32 = blueprints.iter().map(|blueprint| blueprint.min_count).sum();
+    let impossible_count = blueprints.iter().any(|blueprint| {
+        blueprint
+            .max_count
+            .is_some_and(|max_count| max_count < blueprint.min_count)
+    });
+    if required_rooms > params.room_count || impossible_count {
+        return Err(MapGenerationError::UnsatisfiableConstraints);
+    }
+
+    let mut most_placed = 1;
+    let mut broke_constraints = false;
+    for _ in 0..params.max_attempts {
+        match place_rooms(&blueprints, entrance, params, rng) {
+            Ok(mut layout) => {
+                lock_doors(&mut layout, params, rng);
+                if layout.is_completable() {
+                    return Ok(layout);
+                }
+                broke_constraints = true;
+            }
+            Err(AttemptFailure::OutOfDoors(placed)) => most_placed = most_placed.max(placed),
+            Err(AttemptFailure::BrokenConstraints) => broke_constraints = true,
+        }
+    }
+
+    if broke_constraints {
+        return Err(MapGenerationError::UnsatisfiableConstraints);
+    }
+    Err(MapGenerationError::UnsatisfiableRoomCount {
+        requested: params.room_count,
+        placed: most_placed as u32,
+    })
+}
+
+/// An open door of an already placed room that leads to an empty spot on the grid.
+struct DoorSlot {
+    /// The cell the door is in.
+    cell: IVec3,
+    direction: RoomConnectionDirection,
+    /// The depth of the room the door belongs to.
+    depth: u32,
+}
+
+impl DoorSlot {
+    /// The empty spot the door leads to.
+    fn target(&self) -> IVec3 {
+        self.cell.add(connection_direction_dif(&self.direction))
+    }
+}
+
+/// Make a single attempt at laying out the house.
+///
+/// Rooms are grown out of the frontier of open doors. Each step picks an open door and then picks,
+/// by weight, a blueprint that has an exit facing back at it, whose footprint fits behind the door
+/// and whose constraints allow it there, so every step places a room unless nothing can fit that
+/// door.
+fn place_rooms(
+    blueprints: &[&RoomBlueprint],
+    entrance: &RoomBlueprint,
+    params: &LayoutParams,
+    rng: &mut impl Rng,
+) -> Result<HouseLayout, AttemptFailure> {
+    let mut map = PlacedRooms::default();
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    let mut frontier: Vec<DoorSlot> = vec![];
+    place_room(
+        params,
+        &mut map,
+        &mut counts,
+        &mut frontier,
+        IVec3::ZERO,
+        entrance,
+        0,
+    );
+
+    while map.rooms.len() < params.room_count as usize {
+        if frontier.is_empty() {
+            // Every door of every room is already taken, so the house can't grow any further.
+            return Err(AttemptFailure::OutOfDoors(map.rooms.len()));
+        }
+
+        let count = |blueprint: &RoomBlueprint| {
+            counts
+                .get(blueprint.room_name.as_str())
+                .copied()
+                .unwrap_or(0)
+        };
+
+        // Once there are only as many rooms left to place as there are required rooms missing,
+        // every remaining room has to go towards filling those requirements.
+        let missing_required: u32 = blueprints
+            .iter()
+            .map(|blueprint| blueprint.min_count.saturating_sub(count(blueprint)))
+            .sum();
+        let rooms_left = (params.room_count as usize - map.rooms.len()) as u32;
+        let only_required = rooms_left <= missing_required;
+
+        let slot = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+        let depth = slot.depth + 1;
+        let target = slot.target();
+        let opposite = get_opposite_direction(&slot.direction);
+        // Every way a room can be put behind the door, one for each of its exits facing back at
+        // it.
+        let candidates: Vec<(&RoomBlueprint, IVec3)> = blueprints
+            .iter()
+            .copied()
+            .filter(|blueprint| {
+                blueprint.allows_count(count(blueprint))
+                    && blueprint.allows_depth(depth)
+                    && (!only_required || count(blueprint) < blueprint.min_count)
+            })
+            .flat_map(|blueprint| {
+                blueprint
+                    .exits
+                    .iter()
+                    .filter(|exit| exit.direction == opposite)
+                    .map(move |exit| (blueprint, target - exit.cell.extend(0)))
+            })
+            .filter(|(blueprint, position)| map.fits(params, *position, blueprint))
+            .collect();
+
+        // Nothing can attach to this door. Counts only ever go up and the grid only fills up, so
+        // nothing will later on either and it stays dropped from the frontier.
+        let Ok((new_room_blueprint, position)) =
+            candidates.choose_weighted(rng, |(blueprint, _)| {
+                // Rooms with several exits facing the door are no more likely to be picked than
+                // rooms with one.
+                let facing_exits = blueprint
+                    .exits
+                    .iter()
+                    .filter(|exit| exit.direction == opposite)
+                    .count();
+                blueprint.weight / facing_exits as f32
+            })
+        else {
+            continue;
+        };
+
+        place_room(
+            params,
+            &mut map,
+            &mut counts,
+            &mut frontier,
+            *position,
+            new_room_blueprint,
+            depth,
+        );
+    }
+
+    let layout = connect_rooms(&map);
+    if !meets_constraints(&layout, blueprints) {
+        return Err(AttemptFailure::BrokenConstraints);
+    }
+    Ok(layout)
+}
+
+/// Place a room on the grid and update the frontier of open doors to match.
+fn place_room<'a>(
+    params: &LayoutParams,
+    map: &mut PlacedRooms<'a>,
+    counts: &mut HashMap<&'a str, u32>,
+    frontier: &mut Vec<DoorSlot>,
+    position: IVec3,
+    blueprint: &'a RoomBlueprint,
+    depth: u32,
+) {
+    let room_id = RoomId(map.rooms.len() as u32);
+    map.rooms.push(PlacedRoom {
+        position,
+        blueprint,
+    });
+    map.cells
+        .extend(blueprint.cells(position).map(|cell| (cell, room_id)));
+    *counts.entry(blueprint.room_name.as_str()).or_default() += 1;
+
+    frontier.retain(|slot| !map.cells.contains_key(&slot.target()));
+    for exit in blueprint.exits.iter() {
+        let slot = DoorSlot {
+            cell: position + exit.cell.extend(0),
+            direction: exit.direction.clone(),
+            depth,
+        };
+        let target = slot.target();
+        if params.contains(target) && !map.cells.contains_key(&target) {
+            frontier.push(slot);
+        }
+    }
+}
+
+/// Tie all the rooms together through their connections. Two neighbouring rooms are connected
+/// through every pair of exits that face each other.
+fn connect_rooms(map: &PlacedRooms) -> HouseLayout {
+    let rooms: Vec<LayoutRoom> = map
+        .rooms
+        .iter()
+        .enumerate()
+        .map(|(index, room)| {
+            let room_id = RoomId(index as u32);
+            let mut connections = HashMap::new();
+            for exit in room.blueprint.exits.iter() {
+                let target =
+                    room.position + exit.cell.extend(0) + connection_direction_dif(&exit.direction);
+                let Some(&other_room_id) = map.cells.get(&target) else {
+                    continue;
+                };
+                if other_room_id == room_id {
+                    continue;
+                }
+
+                let other_room = &map.rooms[other_room_id.0 as usize];
+                let other_exit = RoomExit {
+                    cell: (target - other_room.position).truncate(),
+                    direction: get_opposite_direction(&exit.direction),
+                };
+                if other_room.blueprint.exits.contains(&other_exit) {
+                    connections.insert(
+                        exit.clone(),
+                        RoomConnection::open(other_room_id, other_exit),
+                    );
+                }
+            }
+
+            LayoutRoom {
+                room_id,
+                position: room.position,
+                footprint: room.blueprint.footprint.clone(),
+                room_name: room.blueprint.room_name.clone(),
+                connections,
+                keys: vec![],
+                taken_props: vec![],
+                corruption: 0.0,
+            }
+        })
+        .collect();
+
+    HouseLayout { seed: None, rooms }
+}
+
+/// Lock [`LayoutParams::locked_doors`] of the layout's doors and hide a key for each of them.
+///
+/// Every key is hidden in a room that can still be reached once its door is locked, so the house
+/// stays completable no matter which doors get locked.
+fn lock_doors(layout: &mut HouseLayout, params: &LayoutParams, rng: &mut impl Rng) {
+    for index in 0..params.locked_doors {
+        // Only look at every door from the side of the room with the lower id, so each door is
+        // only in here once.
+        let mut doors: Vec<(RoomId, RoomExit)> = layout
+            .rooms
+            .iter()
+            .flat_map(|room| {
+                room.connections
+                    .iter()
+                    .filter(|(_, connection)| {
+                        connection.door == DoorKind::Open && connection.room_id.0 > room.room_id.0
+                    })
+                    .map(|(exit, _)| (room.room_id, exit.clone()))
+            })
+            .collect();
+        if doors.is_empty() {
+            return;
+        }
+        // Connections are kept in a hash map, so sort to keep the layout the same for the same rng.
+        doors.sort_by_key(|(room_id, exit)| {
+            (room_id.0, exit.cell.x, exit.cell.y, exit.direction.clone())
+        });
+
+        let (room_id, exit) = doors.swap_remove(rng.gen_range(0..doors.len()));
+        let key_id = KeyId(index);
+        layout.set_door(room_id, &exit, DoorKind::Locked(key_id));
+
+        let reachable: Vec<usize> = layout
+            .reachable_rooms()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, reachable)| reachable.then_some(index))
+            .collect();
+        let key_room = reachable[rng.gen_range(0..reachable.len())];
+        layout.rooms[key_room].keys.push(key_id);
+    }
+}
+
+/// Check the finished layout against the blueprints' constraints.
+///
+/// Rooms are only placed where their depth is allowed, but connecting neighbouring rooms can open
+/// up shortcuts that bring a room closer to the entrance than it was placed.
+fn meets_constraints(layout: &HouseLayout, blueprints: &[&RoomBlueprint]) -> bool {
+    let depths = layout.depths();
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    for (room, depth) in layout.rooms.iter().zip(depths) {
+        *counts.entry(room.room_name.as_str()).or_default() += 1;
+
+        // The entrance is the one room that doesn't need to follow its own depth rules.
+        if room.room_id == RoomId(0) {
+            continue;
+        }
+        let Some(blueprint) = blueprints
+            .iter()
+            .find(|blueprint| blueprint.room_name == room.room_name)
+        else {
+            continue;
+        };
+        if !depth.is_some_and(|depth| blueprint.allows_depth(depth)) {
+            return false;
+        }
+    }
+
+    blueprints.iter().all(|blueprint| {
+        counts
+            .get(blueprint.room_name.as_str())
+            .copied()
+            .unwrap_or(0)
+            >= blueprint.min_count
+    })
+}
+
+fn connection_direction_dif(direction: &RoomConnectionDirection) -> IVec3 {
+    match direction {
+        RoomConnectionDirection::North => IVec3::new(0, 1, 0),
+        RoomConnectionDirection::East => IVec3::new(1, 0, 0),
+        RoomConnectionDirection::South => IVec3::new(0, -1, 0),
+        RoomConnectionDirection::West => IVec3::new(-1, 0, 0),
+        RoomConnectionDirection::Up => IVec3::new(0, 0, 1),
+        RoomConnectionDirection::Down => IVec3::new(0, 0, -1),
+    }
+}
+
+fn get_opposite_direction(direction: &RoomConnectionDirection) -> RoomConnectionDirection {
+    match direction {
+        RoomConnectionDirection::North => RoomConnectionDirection::South,
+        RoomConnectionDirection::East => RoomConnectionDirection::West,
+        RoomConnectionDirection::South => RoomConnectionDirection::North,
+        RoomConnectionDirection::West => RoomConnectionDirection::East,
+        RoomConnectionDirection::Up => RoomConnectionDirection::Down,
+        RoomConnectionDirection::Down => RoomConnectionDirection::Up,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    use RoomConnectionDirection::*;
+
+    /// A single cell blueprint with a weight of `1.0` that can be placed anywhere, any number of
+    /// times.
+    fn blueprint(
+        room_name: &str,
+        allowed_directions: Vec<RoomConnectionDirection>,
+    ) -> RoomBlueprint {
+        let exits = allowed_directions
+            .into_iter()
+            .map(|direction| exit(0, 0, direction))
+            .collect();
+        big_blueprint(room_name, vec![IVec2::ZERO], exits)
+    }
+
+    /// A blueprint covering several cells with a weight of `1.0` that can be placed anywhere, any
+    /// number of times.
+    fn big_blueprint(
+        room_name: &str,
+        footprint: Vec<IVec2>,
+        exits: Vec<RoomExit>,
+    ) -> RoomBlueprint {
+        RoomBlueprint {
+            room_name: room_name.to_string(),
+            footprint,
+            exits,
+            weight: 1.0,
+            min_count: 0,
+            max_count: None,
+            min_depth: 0,
+            max_depth: None,
+        }
+    }
+
+    fn exit(x: i32, y: i32, direction: RoomConnectionDirection) -> RoomExit {
+        RoomExit {
+            cell: IVec2::new(x, y),
+            direction,
+        }
+    }
+
+    /// The same set of rooms that ship in `rooms.assets.json`.
+    fn blueprints() -> Vec<RoomBlueprint> {
+        let mut entrance = blueprint("entrance", vec![North, East, West]);
+        entrance.max_count = Some(1);
+        vec![
+            entrance,
+            blueprint("north-south-hallway", vec![North, South]),
+            blueprint("east-west-hallway", vec![East, West]),
+            blueprint("all-hallway", vec![North, South, East, West]),
+            blueprint("staircase", vec![North, South, Up, Down]),
+            big_blueprint(
+                "ballroom",
+                vec![IVec2::new(0, 0), IVec2::new(1, 0)],
+                vec![
+                    exit(0, 0, South),
+                    exit(0, 0, West),
+                    exit(0, 0, North),
+                    exit(1, 0, North),
+                    exit(1, 0, East),
+                ],
+            ),
+            big_blueprint(
+                "kitchen",
+                vec![IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(0, 1)],
+                vec![
+                    exit(0, 0, South),
+                    exit(1, 0, East),
+                    exit(1, 0, North),
+                    exit(0, 1, North),
+                    exit(0, 1, West),
+                ],
+            ),
+        ]
+    }
+
+    /// Generate a layout for every seed in a range, so each property is checked against many houses.
+    fn layouts(room_count: u32) -> impl Iterator<Item = (u64, HouseLayout)> {
+        let blueprints = blueprints();
+        let params = LayoutParams {
+            room_count,
+            floors_above: 1,
+            floors_below: 1,
+            ..Default::default()
+        };
+        (0..200).map(move |seed| {
+            let layout = generate(&blueprints, &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            (seed, layout)
+        })
+    }
+
+    /// The cells of the grid a room covers.
+    fn cells(room: &LayoutRoom) -> impl Iterator<Item = IVec3> + '_ {
+        room.footprint
+            .iter()
+            .map(|cell| room.position + cell.extend(0))
+    }
+
+    #[test]
+    fn same_seed_same_layout() {
+        let blueprints = blueprints();
+        let params = LayoutParams::default();
+        let a = generate(&blueprints, &params, &mut StdRng::seed_from_u64(42));
+        let b = generate(&blueprints, &params, &mut StdRng::seed_from_u64(42));
+        assert!(a.is_ok());
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn blueprint_order_does_not_matter() {
+        let blueprints = blueprints();
+        let mut reversed = blueprints.clone();
+        reversed.reverse();
+        let params = LayoutParams::default();
+        let a = generate(&blueprints, &params, &mut StdRng::seed_from_u64(7));
+        let b = generate(&reversed, &params, &mut StdRng::seed_from_u64(7));
+        assert!(a.is_ok());
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn entrance_is_first_room_at_origin() {
+        for (seed, layout) in layouts(10) {
+            let entrance = &layout.rooms[0];
+            assert_eq!(entrance.position, IVec3::ZERO, "seed {seed}");
+            assert_eq!(entrance.room_name, "entrance", "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn room_ids_are_contiguous() {
+        for (seed, layout) in layouts(25) {
+            assert_eq!(layout.rooms.len(), 25, "seed {seed}");
+            for (index, room) in layout.rooms.iter().enumerate() {
+                assert_eq!(room.room_id, RoomId(index as u32), "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn no_overlapping_rooms() {
+        for (seed, layout) in layouts(25) {
+            let cells: Vec<IVec3> = layout.rooms.iter().flat_map(cells).collect();
+            let unique_cells: HashSet<IVec3> = cells.iter().copied().collect();
+            assert_eq!(unique_cells.len(), cells.len(), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn no_room_at_negative_y() {
+        for (seed, layout) in layouts(25) {
+            for room in layout.rooms.iter() {
+                assert!(
+                    cells(room).all(|cell| cell.y >= 0),
+                    "seed {seed}: {:?}",
+                    room
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn connections_are_reciprocal() {
+        for (seed, layout) in layouts(25) {
+            for room in layout.rooms.iter() {
+                for (exit, connection) in room.connections.iter() {
+                    let other = &layout.rooms[connection.room_id.0 as usize];
+                    assert_eq!(
+                        other.position + connection.other_exit.cell.extend(0),
+                        room.position
+                            + exit.cell.extend(0)
+                            + connection_direction_dif(&exit.direction),
+                        "seed {seed}"
+                    );
+                    assert_eq!(
+                        connection.other_exit.direction,
+                        get_opposite_direction(&exit.direction),
+                        "seed {seed}"
+                    );
+                    assert_eq!(
+                        other.connections.get(&connection.other_exit),
+                        Some(&RoomConnection {
+                            room_id: room.room_id,
+                            other_exit: exit.clone(),
+                            door: connection.door,
+                        }),
+                        "seed {seed}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_room_reachable_from_entrance() {
+        for (seed, layout) in layouts(25) {
+            let mut visited = HashSet::new();
+            let mut stack = vec![RoomId(0)];
+            while let Some(room_id) = stack.pop() {
+                if !visited.insert(room_id) {
+                    continue;
+                }
+                stack.extend(
+                    layout.rooms[room_id.0 as usize]
+                        .connections
+                        .values()
+                        .map(|connection| connection.room_id),
+                );
+            }
+            assert_eq!(visited.len(), layout.rooms.len(), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn empty_manifest_is_an_error() {
+        let result = generate(&[], &LayoutParams::default(), &mut StdRng::seed_from_u64(0));
+        assert_eq!(result, Err(MapGenerationError::EmptyManifest));
+    }
+
+    #[test]
+    fn invalid_weights_are_an_error() {
+        for weight in [-1.0, f32::NAN, f32::INFINITY] {
+            let mut blueprints = blueprints();
+            blueprints[1].weight = weight;
+            let room_name = blueprints[1].room_name.clone();
+            let result = generate(
+                &blueprints,
+                &LayoutParams::default(),
+                &mut StdRng::seed_from_u64(0),
+            );
+            assert_eq!(result, Err(MapGenerationError::InvalidWeight(room_name)));
+        }
+    }
+
+    #[test]
+    fn missing_entrance_is_an_error() {
+        let params = LayoutParams {
+            entrance: "foyer".to_string(),
+            ..Default::default()
+        };
+        let result = generate(&blueprints(), &params, &mut StdRng::seed_from_u64(0));
+        assert_eq!(
+            result,
+            Err(MapGenerationError::MissingDefinition("foyer".to_string()))
+        );
+    }
+
+    #[test]
+    fn unsatisfiable_room_count_terminates() {
+        // The only room besides the entrance is a dead end, so the house can never grow past two
+        // rooms.
+        let mut entrance = blueprint("entrance", vec![North, East, West]);
+        entrance.max_count = Some(1);
+        let blueprints = vec![entrance, blueprint("closet", vec![South])];
+        let params = LayoutParams {
+            room_count: 50,
+            ..Default::default()
+        };
+        let result = generate(&blueprints, &params, &mut StdRng::seed_from_u64(0));
+        assert_eq!(
+            result,
+            Err(MapGenerationError::UnsatisfiableRoomCount {
+                requested: 50,
+                placed: 2
+            })
+        );
+    }
+
+    #[test]
+    fn large_house_in_one_attempt() {
+        let params = LayoutParams {
+            room_count: 100,
+            max_attempts: 1,
+            ..Default::default()
+        };
+        for seed in 0..50 {
+            let layout = generate(&blueprints(), &params, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(
+                layout.map(|layout| layout.rooms.len()),
+                Ok(100),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn thousands_of_rooms() {
+        let params = LayoutParams {
+            room_count: 5000,
+            floors_above: 1,
+            floors_below: 1,
+            locked_doors: 10,
+            ..Default::default()
+        };
+        for seed in 0..3 {
+            let layout =
+                generate(&blueprints(), &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert_eq!(layout.rooms.len(), 5000, "seed {seed}");
+            assert_eq!(layout.rooms[4999].room_id, RoomId(4999), "seed {seed}");
+            let cells: Vec<IVec3> = layout.rooms.iter().flat_map(cells).collect();
+            let unique_cells: HashSet<IVec3> = cells.iter().copied().collect();
+            assert_eq!(unique_cells.len(), cells.len(), "seed {seed}");
+            assert!(layout.is_completable(), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn zero_weight_rooms_are_never_picked() {
+        let mut blueprints = blueprints();
+        for blueprint in blueprints.iter_mut() {
+            if blueprint.room_name == "east-west-hallway" {
+                blueprint.weight = 0.0;
+            }
+        }
+        let params = LayoutParams {
+            room_count: 25,
+            ..Default::default()
+        };
+        for seed in 0..50 {
+            let layout = generate(&blueprints, &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert!(layout
+                .rooms
+                .iter()
+                .all(|room| room.room_name != "east-west-hallway"));
+        }
+    }
+
+    /// Count how many times each room appears in a layout.
+    fn counts(layout: &HouseLayout) -> HashMap<&str, u32> {
+        let mut counts = HashMap::new();
+        for room in layout.rooms.iter() {
+            *counts.entry(room.room_name.as_str()).or_default() += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn max_count_is_respected() {
+        let mut blueprints = blueprints();
+        for blueprint in blueprints.iter_mut() {
+            match blueprint.room_name.as_str() {
+                "entrance" => blueprint.max_count = Some(1),
+                "all-hallway" => blueprint.max_count = Some(3),
+                _ => {}
+            }
+        }
+        let params = LayoutParams {
+            room_count: 25,
+            ..Default::default()
+        };
+        for seed in 0..50 {
+            let layout = generate(&blueprints, &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            let counts = counts(&layout);
+            assert_eq!(counts.get("entrance"), Some(&1), "seed {seed}");
+            assert!(
+                counts.get("all-hallway").copied().unwrap_or(0) <= 3,
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn min_count_is_respected() {
+        let mut blueprints = blueprints();
+        let mut library = blueprint("library", vec![South]);
+        library.min_count = 1;
+        library.max_count = Some(1);
+        blueprints.push(library);
+        for blueprint in blueprints.iter_mut() {
+            if blueprint.room_name == "north-south-hallway" {
+                blueprint.min_count = 3;
+            }
+        }
+        let params = LayoutParams {
+            room_count: 12,
+            ..Default::default()
+        };
+        for seed in 0..50 {
+            let layout = generate(&blueprints, &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            let counts = counts(&layout);
+            assert_eq!(counts.get("library"), Some(&1), "seed {seed}");
+            assert!(counts["north-south-hallway"] >= 3, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn depth_is_respected() {
+        let mut blueprints = blueprints();
+        let mut ritual_chamber = blueprint("ritual-chamber", vec![South, East, West]);
+        ritual_chamber.min_count = 1;
+        ritual_chamber.min_depth = 6;
+        blueprints.push(ritual_chamber);
+        let params = LayoutParams {
+            room_count: 20,
+            ..Default::default()
+        };
+        for seed in 0..50 {
+            let layout = generate(&blueprints, &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            let depths = layout.depths();
+            let chambers: Vec<_> = layout
+                .rooms
+                .iter()
+                .filter(|room| room.room_name == "ritual-chamber")
+                .collect();
+            assert!(!chambers.is_empty(), "seed {seed}");
+            for room in chambers {
+                assert!(depths[room.room_id.0 as usize].unwrap() >= 6, "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_required_rooms_is_an_error() {
+        let mut blueprints = blueprints();
+        for blueprint in blueprints.iter_mut() {
+            blueprint.min_count = 5;
+        }
+        let result = generate(
+            &blueprints,
+            &LayoutParams::default(),
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert_eq!(result, Err(MapGenerationError::UnsatisfiableConstraints));
+    }
+
+    #[test]
+    fn rooms_stay_within_floors() {
+        let mut floors_used = HashSet::new();
+        for (seed, layout) in layouts(25) {
+            for room in layout.rooms.iter() {
+                assert!(
+                    (-1..=1).contains(&room.position.z),
+                    "seed {seed}: {:?}",
+                    room
+                );
+                floors_used.insert(room.position.z);
+            }
+        }
+        assert_eq!(floors_used.len(), 3);
+    }
+
+    #[test]
+    fn single_floor_house_has_no_stairs() {
+        let params = LayoutParams {
+            room_count: 25,
+            ..Default::default()
+        };
+        for seed in 0..50 {
+            let layout =
+                generate(&blueprints(), &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            for room in layout.rooms.iter() {
+                assert_eq!(room.position.z, 0, "seed {seed}");
+                assert!(
+                    room.connections
+                        .keys()
+                        .all(|exit| exit.direction != Up && exit.direction != Down),
+                    "seed {seed}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn locked_houses_are_completable() {
+        let params = LayoutParams {
+            room_count: 25,
+            floors_above: 1,
+            floors_below: 1,
+            locked_doors: 3,
+            ..Default::default()
+        };
+        for seed in 0..200 {
+            let layout =
+                generate(&blueprints(), &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert!(layout.is_completable(), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn every_lock_has_one_key() {
+        let params = LayoutParams {
+            room_count: 25,
+            locked_doors: 3,
+            ..Default::default()
+        };
+        for seed in 0..200 {
+            let layout =
+                generate(&blueprints(), &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            let mut locks: Vec<KeyId> = layout
+                .rooms
+                .iter()
+                .flat_map(|room| room.connections.values())
+                .filter_map(|connection| match connection.door {
+                    DoorKind::Locked(key_id) => Some(key_id),
+                    _ => None,
+                })
+                .collect();
+            locks.sort();
+            locks.dedup();
+            let mut keys: Vec<KeyId> = layout
+                .rooms
+                .iter()
+                .flat_map(|room| room.keys.iter().copied())
+                .collect();
+            keys.sort();
+            assert_eq!(locks, vec![KeyId(0), KeyId(1), KeyId(2)], "seed {seed}");
+            assert_eq!(keys, locks, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn key_behind_its_own_door_is_not_completable() {
+        let room = |room_id: u32, x: i32, keys: Vec<KeyId>| LayoutRoom {
+            room_id: RoomId(room_id),
+            position: IVec3::new(x, 0, 0),
+            footprint: vec![IVec2::ZERO],
+            room_name: "east-west-hallway".to_string(),
+            connections: HashMap::new(),
+            keys,
+            taken_props: vec![],
+            corruption: 0.0,
+        };
+        let mut layout = HouseLayout {
+            seed: None,
+            rooms: vec![room(0, 0, vec![]), room(1, 1, vec![KeyId(0)])],
+        };
+        layout.rooms[0].connections.insert(
+            exit(0, 0, East),
+            RoomConnection {
+                room_id: RoomId(1),
+                other_exit: exit(0, 0, West),
+                door: DoorKind::Locked(KeyId(0)),
+            },
+        );
+        layout.rooms[1].connections.insert(
+            exit(0, 0, West),
+            RoomConnection {
+                room_id: RoomId(0),
+                other_exit: exit(0, 0, East),
+                door: DoorKind::Locked(KeyId(0)),
+            },
+        );
+        assert!(!layout.is_completable());
+
+        // Moving the key to the entrance makes it completable again.
+        let key = layout.rooms[1].keys.pop().unwrap();
+        layout.rooms[0].keys.push(key);
+        assert!(layout.is_completable());
+
+        layout.set_door(RoomId(0), &exit(0, 0, East), DoorKind::Sealed);
+        assert!(!layout.is_completable());
+    }
+
+    #[test]
+    fn swapped_rooms_keep_the_layout_connected() {
+        for (seed, mut layout) in layouts(25).take(50) {
+            let swappable: Vec<(RoomId, RoomId)> = layout
+                .rooms
+                .iter()
+                .flat_map(|a| layout.rooms.iter().map(move |b| (a.room_id, b.room_id)))
+                .filter(|(a, b)| a.0 != 0 && b.0 != 0 && layout.can_swap_rooms(*a, *b))
+                .collect();
+            let Some((a, b)) = swappable.first().copied() else {
+                continue;
+            };
+            let position_a = layout.rooms[a.0 as usize].position;
+            let position_b = layout.rooms[b.0 as usize].position;
+            assert!(layout.swap_rooms(a, b));
+            assert_eq!(
+                layout.rooms[a.0 as usize].position, position_b,
+                "seed {seed}"
+            );
+            assert_eq!(
+                layout.rooms[b.0 as usize].position, position_a,
+                "seed {seed}"
+            );
+            assert!(layout.is_completable(), "seed {seed}");
+            for room in &layout.rooms {
+                for (exit, connection) in &room.connections {
+                    let other = &layout.rooms[connection.room_id.0 as usize];
+                    let back = &other.connections[&connection.other_exit];
+                    assert_eq!(back.room_id, room.room_id, "seed {seed}");
+                    assert_eq!(&back.other_exit, exit, "seed {seed}");
+                }
+            }
+        }
+    }
+
+    /// Check a layout against [`blueprints`]
+    fn check(layout: &HouseLayout) -> Result<(), MapGenerationError> {
+        let blueprints = blueprints();
+        layout.check(|room_name| {
+            blueprints
+                .iter()
+                .find(|blueprint| blueprint.room_name == room_name)
+                .map(|blueprint| blueprint.footprint.as_slice())
+        })
+    }
+
+    #[test]
+    fn generated_layouts_pass_the_check() {
+        for (seed, layout) in layouts(15).take(50) {
+            assert_eq!(check(&layout), Ok(()), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn misnumbered_rooms_fail_the_check() {
+        let (_, mut layout) = layouts(10).next().unwrap();
+        layout.rooms.swap(1, 2);
+        assert!(matches!(
+            check(&layout),
+            Err(MapGenerationError::InvalidLayout(_))
+        ));
+    }
+
+    #[test]
+    fn broken_connections_fail_the_check() {
+        let (_, layout) = layouts(10).next().unwrap();
+        let (exit, connection) = layout.rooms[0]
+            .connections
+            .iter()
+            .map(|(exit, connection)| (exit.clone(), connection.clone()))
+            .next()
+            .unwrap();
+
+        let mut missing_room = layout.clone();
+        missing_room.rooms[0]
+            .connections
+            .get_mut(&exit)
+            .unwrap()
+            .room_id = RoomId(100);
+        assert!(matches!(
+            check(&missing_room),
+            Err(MapGenerationError::InvalidLayout(_))
+        ));
+
+        let mut one_way = layout.clone();
+        one_way.rooms[connection.room_id.0 as usize]
+            .connections
+            .remove(&connection.other_exit);
+        assert!(matches!(
+            check(&one_way),
+            Err(MapGenerationError::InvalidLayout(_))
+        ));
+
+        let mut mismatched_door = layout;
+        mismatched_door.rooms[0]
+            .connections
+            .get_mut(&exit)
+            .unwrap()
+            .door = DoorKind::Sealed;
+        assert!(matches!(
+            check(&mismatched_door),
+            Err(MapGenerationError::InvalidLayout(_))
+        ));
+    }
+
+    #[test]
+    fn wrong_footprints_and_unknown_rooms_fail_the_check() {
+        let (_, layout) = layouts(10).next().unwrap();
+
+        let mut wrong_footprint = layout.clone();
+        wrong_footprint.rooms[0].footprint.push(IVec2::Y);
+        assert!(matches!(
+            check(&wrong_footprint),
+            Err(MapGenerationError::InvalidLayout(_))
+        ));
+
+        let mut unknown_room = layout;
+        unknown_room.rooms[0].room_name = "foyer".to_string();
+        assert_eq!(
+            check(&unknown_room),
+            Err(MapGenerationError::MissingDefinition("foyer".to_string()))
+        );
+    }
+
+    #[test]
+    fn rooms_with_different_exits_cant_swap() {
+        let blueprints = blueprints();
+        let params = LayoutParams {
+            room_count: 25,
+            ..Default::default()
+        };
+        let mut layout = generate(&blueprints, &params, &mut StdRng::seed_from_u64(0)).unwrap();
+        let before = layout.clone();
+        let different = layout
+            .rooms
+            .iter()
+            .find(|room| room.connections.len() != layout.rooms[0].connections.len())
+            .map(|room| room.room_id)
+            .unwrap();
+        assert!(!layout.swap_rooms(RoomId(0), different));
+        assert!(!layout.swap_rooms(RoomId(0), RoomId(0)));
+        assert_eq!(layout, before);
+    }
+
+    #[test]
+    fn reachability_from_a_room_uses_held_keys() {
+        let mut layout = HouseLayout::default();
+        for (room_id, x) in [(0, 0), (1, 1), (2, 2)] {
+            layout.rooms.push(LayoutRoom {
+                room_id: RoomId(room_id),
+                position: IVec3::new(x, 0, 0),
+                footprint: vec![IVec2::ZERO],
+                room_name: "east-west-hallway".to_string(),
+                connections: HashMap::new(),
+                keys: vec![],
+                taken_props: vec![],
+                corruption: 0.0,
+            });
+        }
+        for (from, to, door) in [(0, 1, DoorKind::Open), (1, 2, DoorKind::Locked(KeyId(0)))] {
+            layout.rooms[from].connections.insert(
+                exit(0, 0, East),
+                RoomConnection {
+                    room_id: RoomId(to as u32),
+                    other_exit: exit(0, 0, West),
+                    door,
+                },
+            );
+            layout.rooms[to].connections.insert(
+                exit(0, 0, West),
+                RoomConnection {
+                    room_id: RoomId(from as u32),
+                    other_exit: exit(0, 0, East),
+                    door,
+                },
+            );
+        }
+
+        assert_eq!(
+            layout.reachable_rooms_from(RoomId(1), &HashSet::new()),
+            vec![true, true, false]
+        );
+        assert_eq!(
+            layout.reachable_rooms_from(RoomId(1), &HashSet::from([KeyId(0)])),
+            vec![true, true, true]
+        );
+    }
+
+    #[test]
+    fn big_rooms_are_placed() {
+        let mut placed = HashSet::new();
+        for (_, layout) in layouts(25) {
+            placed.extend(
+                layout
+                    .rooms
+                    .iter()
+                    .filter(|room| room.footprint.len() > 1)
+                    .map(|room| room.room_name.clone()),
+            );
+        }
+        assert!(placed.contains("ballroom"));
+        assert!(placed.contains("kitchen"));
+    }
+
+    #[test]
+    fn rooms_can_have_several_exits_in_one_direction() {
+        let several_exits = layouts(25).any(|(_, layout)| {
+            layout.rooms.iter().any(|room| {
+                room.connections
+                    .keys()
+                    .filter(|exit| exit.direction == North)
+                    .count()
+                    > 1
+            })
+        });
+        assert!(several_exits);
+    }
+
+    #[test]
+    fn entrance_has_to_fit_at_origin() {
+        let mut blueprints = blueprints();
+        blueprints[0].footprint.push(IVec2::new(0, -1));
+        let result = generate(
+            &blueprints,
+            &LayoutParams::default(),
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert_eq!(result, Err(MapGenerationError::UnsatisfiableConstraints));
+    }
+
+    #[test]
+    fn layouts_round_trip_through_json() {
+        let params = LayoutParams {
+            locked_doors: 2,
+            ..Default::default()
+        };
+        for seed in 0..50 {
+            let mut layout =
+                generate(&blueprints(), &params, &mut StdRng::seed_from_u64(seed)).unwrap();
+            layout.seed = Some(seed);
+            let json = serde_json::to_string(&layout).unwrap();
+            assert_eq!(
+                serde_json::from_str::<HouseLayout>(&json).unwrap(),
+                layout,
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn hand_made_layout_reads_from_json() {
+        let json = r#"{
+            "rooms": [
+                {
+                    "room_id": 0,
+                    "position": [0, 0, 0],
+                    "footprint": [[0, 0]],
+                    "room_name": "entrance",
+                    "connections": [
+                        {
+                            "exit": { "cell": [0, 0], "direction": "North" },
+                            "room_id": 1,
+                            "other_exit": { "cell": [0, 0], "direction": "South" },
+                            "door": { "Locked": 0 }
+                        }
+                    ],
+                    "keys": [0]
+                },
+                {
+                    "room_id": 1,
+                    "position": [0, 1, 0],
+                    "footprint": [[0, 0]],
+                    "room_name": "north-south-hallway",
+                    "connections": [
+                        {
+                            "exit": { "cell": [0, 0], "direction": "South" },
+                            "room_id": 0,
+                            "other_exit": { "cell": [0, 0], "direction": "North" },
+                            "door": { "Locked": 0 }
+                        }
+                    ]
+                }
+            ]
+        }"#;
+        let layout: HouseLayout = serde_json::from_str(json).unwrap();
+        assert_eq!(layout.seed, None);
+        assert_eq!(layout.rooms.len(), 2);
+        assert_eq!(
+            layout.rooms[1].connections.get(&exit(0, 0, South)),
+            Some(&RoomConnection {
+                room_id: RoomId(0),
+                other_exit: exit(0, 0, North),
+                door: DoorKind::Locked(KeyId(0)),
+            })
+        );
+        assert!(layout.is_completable());
+    }
+
+    #[test]
+    fn the_manor_is_completable() {
+        let json = include_str!("../../assets/houses/manor.house.json");
+        let layout: HouseLayout = serde_json::from_str(json).unwrap();
+        assert!(layout.is_completable());
+    }
+}
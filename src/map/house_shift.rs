@@ -0,0 +1,268 @@
+//! The house rearranges itself while the player isn't looking.
+//!
+//! Sending [`ShiftHouse`] has some of the rooms the player can't see trade places with rooms of the
+//! same shape. A swap is only kept if the player can still get to every room they could get to
+//! before. Rooms keep their ids and entities, so [`House::rooms`](super::House) stays as it is while
+//! each moved room and its neighbours get new connections. [`HouseShifted`] is sent once it is done.
+
+use bevy::{ecs::event::ManualEventReader, prelude::*, utils::HashSet};
+use rand::seq::SliceRandom;
+
+use crate::AppSet;
+
+use super::{
+    doors::HeldKeys,
+    house_graph::{rebuild_house_graph, HouseGraph},
+    load_house::current_house_layout,
+    map_navigation::MapRoomIndex,
+    room_translation, HouseLayout, KeyId, Room, RoomId,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<ShiftHouse>();
+    app.add_event::<HouseShifted>();
+    app.add_systems(
+        Update,
+        (
+            shift_house.run_if(on_event::<ShiftHouse>()),
+            log_house_shifts.run_if(on_event::<HouseShifted>()),
+        )
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Asks the house to move some of the rooms the player can't see
+#[derive(Event, Debug)]
+pub struct ShiftHouse {
+    /// The most pairs of rooms that may trade places
+    pub swaps: usize,
+}
+
+/// Sent when the house has moved rooms around
+#[derive(Event, Debug)]
+pub struct HouseShifted {
+    /// The rooms that moved, in order of their ids
+    pub rooms: Vec<RoomId>,
+}
+
+fn shift_house(world: &mut World, mut reader: Local<ManualEventReader<ShiftHouse>>) {
+    let swaps: usize = reader
+        .read(world.resource::<Events<ShiftHouse>>())
+        .map(|event| event.swaps)
+        .sum();
+    if swaps == 0 {
+        return;
+    }
+    let Some(mut layout) = current_house_layout(world) else {
+        return;
+    };
+    let current_room = world.resource::<MapRoomIndex>().0;
+    let held_keys = world.resource::<HeldKeys>().0.clone();
+
+    // Only rooms that are out of sight can move, and the entrance always stays where it is.
+    let hidden: Vec<RoomId> = world
+        .query_filtered::<(&RoomId, &Visibility), With<Room>>()
+        .iter(world)
+        .filter(|(room_id, visibility)| {
+            **visibility == Visibility::Hidden && **room_id != current_room && room_id.0 != 0
+        })
+        .map(|(room_id, _)| *room_id)
+        .collect();
+    let mut pairs: Vec<(RoomId, RoomId)> = hidden
+        .iter()
+        .flat_map(|a| hidden.iter().map(move |b| (*a, *b)))
+        .filter(|(a, b)| a.0 < b.0 && layout.can_swap_rooms(*a, *b))
+        .collect();
+    pairs.shuffle(&mut rand::thread_rng());
+
+    let mut moved = swap_rooms(&mut layout, &pairs, swaps, current_room, &held_keys);
+    if moved.is_empty() {
+        return;
+    }
+
+    // Put every room where the layout now says it is, along with its doors.
+    let mut rooms = world.query::<(&mut Room, &mut Transform)>();
+    for layout_room in &layout.rooms {
+        let Some(entity) = world.resource::<HouseGraph>().entity(layout_room.room_id) else {
+            continue;
+        };
+        let Ok((mut room, mut transform)) = rooms.get_mut(world, entity) else {
+            continue;
+        };
+        if room.position != layout_room.position {
+            room.position = layout_room.position;
+            transform.translation = room_translation(layout_room.position, &layout_room.footprint);
+        }
+        if room.connections != layout_room.connections {
+            room.connections.clone_from(&layout_room.connections);
+        }
+    }
+    rebuild_house_graph(world);
+
+    moved.sort_by_key(|room_id| room_id.0);
+    world.send_event(HouseShifted { rooms: moved });
+}
+
+/// Have up to `swaps` of the given pairs of rooms trade places, in order. A swap is only kept if the
+/// player, in `current_room` and holding `held_keys`, can still get to every room they could get to
+/// before it. Returns the rooms that moved.
+fn swap_rooms(
+    layout: &mut HouseLayout,
+    pairs: &[(RoomId, RoomId)],
+    swaps: usize,
+    current_room: RoomId,
+    held_keys: &HashSet<KeyId>,
+) -> Vec<RoomId> {
+    let reachable_before = reachable_rooms(layout, current_room, held_keys);
+    let mut moved: Vec<RoomId> = Vec::new();
+    for (a, b) in pairs.iter().copied() {
+        if moved.len() / 2 >= swaps {
+            break;
+        }
+        if moved.contains(&a) || moved.contains(&b) {
+            continue;
+        }
+        let mut shifted = layout.clone();
+        shifted.swap_rooms(a, b);
+        if reachable_before.is_subset(&reachable_rooms(&shifted, current_room, held_keys)) {
+            *layout = shifted;
+            moved.extend([a, b]);
+        }
+    }
+    moved
+}
+
+/// Every room the player can get to from `current_room` while holding `held_keys`
+pub(super) fn reachable_rooms(
+    layout: &HouseLayout,
+    current_room: RoomId,
+    held_keys: &HashSet<KeyId>,
+) -> HashSet<RoomId> {
+    layout
+        .reachable_rooms_from(current_room, held_keys)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, reachable)| *reachable)
+        .map(|(index, _)| RoomId(index as u32))
+        .collect()
+}
+
+fn log_house_shifts(mut events: EventReader<HouseShifted>) {
+    for event in events.read() {
+        info!("The house shifted, moving rooms {:?}", event.rooms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+    use crate::map::{
+        house_layout::LayoutRoom, DoorKind, RoomConnection, RoomConnectionDirection, RoomExit,
+    };
+
+    fn exit(x: i32, direction: RoomConnectionDirection) -> RoomExit {
+        RoomExit {
+            cell: IVec2::new(x, 0),
+            direction,
+        }
+    }
+
+    fn layout_room(
+        room_id: u32,
+        position: IVec3,
+        footprint: Vec<IVec2>,
+        connections: Vec<(RoomExit, RoomConnection)>,
+    ) -> LayoutRoom {
+        LayoutRoom {
+            room_id: RoomId(room_id),
+            position,
+            footprint,
+            room_name: format!("room-{room_id}"),
+            connections: connections.into_iter().collect::<HashMap<_, _>>(),
+            keys: Vec::new(),
+            taken_props: Vec::new(),
+            corruption: 0.0,
+        }
+    }
+
+    /// An entrance two cells wide with a closet north of each cell. The left closet's door is open
+    /// and the right one's is locked, with no key for it anywhere.
+    fn closets() -> HouseLayout {
+        use RoomConnectionDirection::{North, South};
+        let closet = |room_id: u32, x: i32, door: DoorKind| {
+            layout_room(
+                room_id,
+                IVec3::new(x, 1, 0),
+                vec![IVec2::ZERO],
+                vec![(
+                    exit(0, South),
+                    RoomConnection {
+                        room_id: RoomId(0),
+                        other_exit: exit(x, North),
+                        door,
+                    },
+                )],
+            )
+        };
+        let door_to = |room_id: u32, door: DoorKind| RoomConnection {
+            room_id: RoomId(room_id),
+            other_exit: exit(0, South),
+            door,
+        };
+        HouseLayout {
+            seed: None,
+            rooms: vec![
+                layout_room(
+                    0,
+                    IVec3::ZERO,
+                    vec![IVec2::ZERO, IVec2::X],
+                    vec![
+                        (exit(0, North), door_to(1, DoorKind::Open)),
+                        (exit(1, North), door_to(2, DoorKind::Locked(KeyId(0)))),
+                    ],
+                ),
+                closet(1, 0, DoorKind::Open),
+                closet(2, 1, DoorKind::Locked(KeyId(0))),
+            ],
+        }
+    }
+
+    #[test]
+    fn swaps_that_trade_one_reachable_room_for_another_are_refused() {
+        let mut layout = closets();
+        assert!(layout.can_swap_rooms(RoomId(1), RoomId(2)));
+        let before = layout.clone();
+        // The player could still get to two rooms, but not the same two.
+        let moved = swap_rooms(
+            &mut layout,
+            &[(RoomId(1), RoomId(2))],
+            1,
+            RoomId(0),
+            &HashSet::new(),
+        );
+        assert!(moved.is_empty());
+        assert_eq!(layout, before);
+    }
+
+    #[test]
+    fn swaps_that_keep_every_reachable_room_are_kept() {
+        let mut layout = closets();
+        let held_keys = HashSet::from([KeyId(0)]);
+        let moved = swap_rooms(
+            &mut layout,
+            &[(RoomId(1), RoomId(2))],
+            1,
+            RoomId(0),
+            &held_keys,
+        );
+        assert_eq!(moved, [RoomId(1), RoomId(2)]);
+        assert_eq!(layout.rooms[1].position, IVec3::new(1, 1, 0));
+        assert_eq!(
+            reachable_rooms(&layout, RoomId(0), &held_keys),
+            HashSet::from([RoomId(0), RoomId(1), RoomId(2)])
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Spawns houses from a [`HouseLayout`], and turns a spawned house back into one.
+//!
+//! Hand-made houses ship as `.house.json` assets in the same format [`current_house_layout`] is
+//! written out in, and are loaded as [`HouseAsset`]s.
+
+use bevy::{ecs::world::Command, prelude::*, utils::HashMap};
+use bevy_asset_loader::{
+    asset_collection::AssetCollection,
+    loading_state::{
+        config::{ConfigureLoadingState, LoadingStateConfig},
+        LoadingStateAppExt,
+    },
+};
+use bevy_common_assets::json::JsonAssetPlugin;
+use leafwing_manifest::{identifier::Id, manifest::Manifest};
+use serde::Deserialize;
+
+use crate::{screens::Screen, AppLoadingState};
+
+use super::{
+    corruption::Corruption,
+    generate_map::MapGenerationFailed,
+    house_graph::rebuild_house_graph,
+    house_layout::{HouseLayout, LayoutRoom, MapGenerationError},
+    manifest::RoomDefinitionManifest,
+    room_animation::RoomAnimation,
+    House, HouseSeed, Room, RoomId, SpawnRoom,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(JsonAssetPlugin::<HouseAsset>::new(&["house.json"]));
+    app.configure_loading_state(
+        LoadingStateConfig::new(AppLoadingState::Loading).load_collection::<HouseAssets>(),
+    );
+}
+
+/// A hand-made house
+#[derive(Asset, TypePath, Deserialize, Debug)]
+#[serde(transparent)]
+pub struct HouseAsset(pub HouseLayout);
+
+/// The hand-made houses that ship with the game
+#[derive(Resource, AssetCollection, Reflect, Clone)]
+pub struct HouseAssets {
+    #[asset(path = "houses/manor.house.json")]
+    pub manor: Handle<HouseAsset>,
+}
+
+/// The hand-made house to play in the next run, instead of generating one
+#[derive(Resource, Debug)]
+pub struct HandMadeHouse(pub Handle<HouseAsset>);
+
+/// Spawns the exact house described by a [`HouseLayout`], such as a hand-made house or one written
+/// out with [`current_house_layout`].
+///
+/// If the layout doesn't pass [`HouseLayout::check`], uses a room whose definition has no idle
+/// texture, or is for a new run that can't be completed, a [`MapGenerationFailed`] event is sent
+/// instead.
+pub struct LoadHouse {
+    pub layout: HouseLayout,
+    /// Whether the house starts a new run, which the player has to be able to get through all of.
+    /// A saved run may have had its keys picked up and doors sealed since it started.
+    pub new_run: bool,
+}
+
+impl Command for LoadHouse {
+    fn apply(self, world: &mut World) {
+        if let Err(error) = check_layout(world, &self.layout, self.new_run) {
+            world.send_event(MapGenerationFailed {
+                seed: self.layout.seed,
+                error,
+            });
+            return;
+        }
+
+        let house = world
+            .spawn((
+                House {
+                    rooms: HashMap::new(),
+                },
+                VisibilityBundle::default(),
+                TransformBundle::from_transform(Transform::from_translation(Vec3::splat(0.0))),
+                StateScoped(Screen::Gameplay),
+            ))
+            .id();
+        if let Some(seed) = self.layout.seed {
+            world.entity_mut(house).insert(HouseSeed(seed));
+        }
+
+        for room in self.layout.rooms {
+            SpawnRoom {
+                house_entity: house,
+                room_id: room.room_id,
+                room_position: room.position,
+                room_connections: room.connections,
+                room_def_id: room.room_name,
+                room_keys: room.keys,
+                room_taken_props: room.taken_props,
+                room_corruption: room.corruption,
+            }
+            .apply(world);
+        }
+        rebuild_house_graph(world);
+    }
+}
+
+/// Make sure the layout is one that can be spawned, and that every room in it has a definition to
+/// spawn it from along with the idle texture every room starts out showing. Houses for a new run
+/// also have to be completable.
+fn check_layout(
+    world: &World,
+    layout: &HouseLayout,
+    new_run: bool,
+) -> Result<(), MapGenerationError> {
+    let Some(room_assets) = world.get_resource::<RoomDefinitionManifest>() else {
+        return Err(MapGenerationError::EmptyManifest);
+    };
+    layout.check(|room_name| {
+        room_assets
+            .get(Id::from_name(room_name))
+            .map(|definition| definition.footprint.as_slice())
+    })?;
+    for room in &layout.rooms {
+        let Some(definition) = room_assets.get(Id::from_name(&room.room_name)) else {
+            return Err(MapGenerationError::MissingDefinition(
+                room.room_name.clone(),
+            ));
+        };
+        if !definition
+            .art_collection
+            .textures
+            .contains_key(RoomAnimation::IDLE)
+        {
+            return Err(MapGenerationError::MissingTexture {
+                room_name: room.room_name.clone(),
+                texture: RoomAnimation::IDLE.to_string(),
+            });
+        }
+    }
+    if new_run && !layout.is_completable() {
+        return Err(MapGenerationError::InvalidLayout(
+            "the player can't get to every room".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Describe the house that is currently spawned as a [`HouseLayout`], including the doors that have
+/// been opened, the keys and props that have been picked up and how corrupted the rooms have become
+/// since it was spawned.
+pub fn current_house_layout(world: &mut World) -> Option<HouseLayout> {
+    let (house, seed) = world
+        .query::<(&House, Option<&HouseSeed>)>()
+        .get_single(world)
+        .ok()?;
+    let seed = seed.map(|seed| seed.0);
+    let room_entities: Vec<Entity> = house.rooms.values().copied().collect();
+    let room_assets = world.get_resource::<RoomDefinitionManifest>()?;
+
+    let mut rooms: Vec<LayoutRoom> = room_entities
+        .into_iter()
+        .filter_map(|entity| {
+            let room_id = *world.get::<RoomId>(entity)?;
+            let room = world.get::<Room>(entity)?;
+            let definition = room_assets.get(room.room_def_id)?;
+            Some(LayoutRoom {
+                room_id,
+                position: room.position,
+                footprint: definition.footprint.clone(),
+                room_name: definition.room_name.clone(),
+                connections: room.connections.clone(),
+                keys: room.keys.clone(),
+                taken_props: room.taken_props.clone(),
+                corruption: world
+                    .get::<Corruption>(entity)
+                    .map_or(0.0, Corruption::amount),
+            })
+        })
+        .collect();
+    rooms.sort_by_key(|room| room.room_id.0);
+
+    Some(HouseLayout { seed, rooms })
+}
@@ -8,7 +8,7 @@ use leafwing_manifest::{
 use serde::{Deserialize, Serialize};
 use toa_animator::{Animations, ArtCollection, TextureAsset};
 
-use super::{RoomConnectionDirection, RoomDefinition};
+use super::{props::RawPropDefinition, RoomConnectionDirection, RoomDefinition, RoomExit};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<SimpleAssetState>()
@@ -19,7 +19,38 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RawRoomDefinition {
     pub room_name: String,
+    /// Shorthand for exits on the room's first cell, which is all a single cell room needs.
+    #[serde(default)]
     pub allowed_directions: Vec<RoomConnectionDirection>,
+    /// The cells the room covers. Defaults to a single cell.
+    #[serde(default)]
+    pub footprint: Vec<IVec2>,
+    /// Exits on any of the room's cells, on top of the ones from `allowed_directions`.
+    #[serde(default)]
+    pub exits: Vec<RoomExit>,
+    /// How likely this room is to be picked during generation. Defaults to `1.0`.
+    pub weight: Option<f32>,
+    /// The fewest times this room has to appear in a house.
+    pub min_count: Option<u32>,
+    /// The most times this room may appear in a house.
+    pub max_count: Option<u32>,
+    /// Shorthand for a `max_count` of one.
+    #[serde(default)]
+    pub unique: bool,
+    /// The fewest steps away from the entrance this room may be.
+    pub min_depth: Option<u32>,
+    /// The most steps away from the entrance this room may be.
+    pub max_depth: Option<u32>,
+    /// The things in the room that can be interacted with.
+    #[serde(default)]
+    pub props: Vec<RawPropDefinition>,
+    /// How much sanity the player loses on entering this room, on top of what every room costs.
+    /// Defaults to `0.0`.
+    pub sanity_drain: Option<f32>,
+    /// The animation shown at each level of corruption past the first, such as `disturbed` and then
+    /// `corrupted`. Each needs a texture of the same name.
+    #[serde(default)]
+    pub corruption_animations: Vec<String>,
     pub textures: HashMap<String, TextureAsset>,
     pub animations: Animations,
 }
@@ -67,7 +98,36 @@ impl Manifest for RoomDefinitionManifest {
                     .collect();
                 let item = RoomDefinition {
                     room_name: raw_item.room_name,
-                    allowed_directions: raw_item.allowed_directions,
+                    footprint: if raw_item.footprint.is_empty() {
+                        vec![IVec2::ZERO]
+                    } else {
+                        raw_item.footprint
+                    },
+                    exits: raw_item
+                        .allowed_directions
+                        .into_iter()
+                        .map(|direction| RoomExit {
+                            cell: IVec2::ZERO,
+                            direction,
+                        })
+                        .chain(raw_item.exits)
+                        .collect(),
+                    weight: raw_item.weight.unwrap_or(1.0),
+                    min_count: raw_item.min_count.unwrap_or(0),
+                    max_count: if raw_item.unique {
+                        Some(1)
+                    } else {
+                        raw_item.max_count
+                    },
+                    min_depth: raw_item.min_depth.unwrap_or(0),
+                    max_depth: raw_item.max_depth,
+                    props: raw_item
+                        .props
+                        .into_iter()
+                        .map(|prop| prop.load(asset_server))
+                        .collect(),
+                    sanity_drain: raw_item.sanity_drain.unwrap_or(0.0),
+                    corruption_animations: raw_item.corruption_animations,
                     art_collection: ArtCollection {
                         animations: raw_item.animations,
                         textures,
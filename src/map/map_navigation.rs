@@ -1,22 +1,43 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 
-use crate::screens::Screen;
+use crate::{screens::Screen, AppSet};
 
-use super::{Room, RoomId};
+use super::{
+    doors::DoorBlocked,
+    house_graph::HouseGraph,
+    room_transition::{RoomTransition, RoomTransitionSystems},
+    DoorKind, HeldKeys, Room, RoomConnection, RoomConnectionDirection, RoomId,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.insert_resource(MapRoomIndex(RoomId(0)));
+    app.init_resource::<ExitCycle>();
     app.add_systems(OnEnter(Screen::Gameplay), |mut commands: Commands| {
+        commands.insert_resource(ExitCycle::default());
+    });
+    // Reset on the way out rather than on the way in, so a resumed run can put the player back in
+    // their room before the house is spawned.
+    app.add_systems(OnExit(Screen::Gameplay), |mut commands: Commands| {
         commands.insert_resource(MapRoomIndex(RoomId(0)));
     });
 
     app.add_systems(
         Update,
-        (change_room_index, move_room_to_camera)
+        (
+            move_in_direction.run_if(on_event::<MoveInDirection>()),
+            (change_room_index, change_focused_room)
+                .chain()
+                .run_if(on_event::<ChangeRoom>()),
+        )
             .chain()
-            .run_if(on_event::<ChangeRoom>()),
+            // The transition into the new room has to have started before the next move is taken,
+            // so that move is refused until the player has arrived.
+            .before(RoomTransitionSystems)
+            .in_set(AppSet::Update),
     );
     app.add_event::<ChangeRoom>();
+    app.add_event::<MoveInDirection>();
+    app.add_event::<RoomTraversed>();
 }
 
 #[derive(Resource)]
@@ -27,10 +48,98 @@ pub struct ChangeRoom {
     pub new_room_id: RoomId,
 }
 
+/// Sent when the player walks from one room into another, as opposed to being put in a room
+/// directly such as when a run is resumed
+#[derive(Event, Debug)]
+pub struct RoomTraversed {
+    pub to: RoomId,
+}
+
+/// Asks to leave the current room in the given direction. Sends a [`ChangeRoom`] and a
+/// [`RoomTraversed`] if the player can go that way, or a [`DoorBlocked`] if the door is in their way.
+/// Rooms with several exits that way are left through each of them in turn.
+#[derive(Event, Debug)]
+pub struct MoveInDirection {
+    pub direction: RoomConnectionDirection,
+}
+
 #[derive(Component, Reflect, Debug)]
 #[reflect(Component)]
 pub struct FocusedRoom;
 
+/// Which exit was last taken out of each room in each direction.
+///
+/// Big rooms can have several exits in the same direction. Each move that way takes the next exit
+/// after the one taken last time, so moving the same way again leads through another door.
+#[derive(Resource, Debug, Default)]
+struct ExitCycle(HashMap<(RoomId, RoomConnectionDirection), usize>);
+
+impl ExitCycle {
+    /// The connection to take out of a room in the given direction, which is the first exit after the
+    /// one taken last time that `can_pass` allows. `None` if none of them can be passed.
+    fn next_exit<'a>(
+        &mut self,
+        room_id: RoomId,
+        room: &'a Room,
+        direction: &RoomConnectionDirection,
+        can_pass: impl Fn(&DoorKind) -> bool,
+    ) -> Option<&'a RoomConnection> {
+        let exits = room.exits(direction);
+        let key = (room_id, direction.clone());
+        let start = self.0.get(&key).map_or(0, |last| last + 1);
+        let index = (0..exits.len())
+            .map(|offset| (start + offset) % exits.len())
+            .find(|index| can_pass(&exits[*index].1.door))?;
+        self.0.insert(key, index);
+        Some(exits[index].1)
+    }
+}
+
+fn move_in_direction(
+    mut events: EventReader<MoveInDirection>,
+    mut change_room_writer: EventWriter<ChangeRoom>,
+    mut traversed_writer: EventWriter<RoomTraversed>,
+    mut door_blocked_writer: EventWriter<DoorBlocked>,
+    room_res: Res<MapRoomIndex>,
+    held_keys: Res<HeldKeys>,
+    mut exit_cycle: ResMut<ExitCycle>,
+    transition: Option<Res<RoomTransition>>,
+    house_graph: Res<HouseGraph>,
+    rooms: Query<&Room>,
+) {
+    // Only one move is taken at a time, and none until the player has arrived in the room.
+    let Some(event) = events.read().last() else {
+        return;
+    };
+    if transition.is_some() {
+        return;
+    }
+    let Some(room) = house_graph
+        .entity(room_res.0)
+        .and_then(|entity| rooms.get(entity).ok())
+    else {
+        return;
+    };
+    let Some(connection) = exit_cycle.next_exit(room_res.0, room, &event.direction, |door| {
+        held_keys.can_pass(door)
+    }) else {
+        if let Some((exit, connection)) = room.exits(&event.direction).first() {
+            door_blocked_writer.send(DoorBlocked {
+                room_id: room_res.0,
+                exit: (*exit).clone(),
+                door: connection.door,
+            });
+        }
+        return;
+    };
+    change_room_writer.send(ChangeRoom {
+        new_room_id: connection.room_id,
+    });
+    traversed_writer.send(RoomTraversed {
+        to: connection.room_id,
+    });
+}
+
 fn change_room_index(mut events: EventReader<ChangeRoom>, mut room_index: ResMut<MapRoomIndex>) {
     for event in events.read() {
         println!("room changed to: {:?}", event.new_room_id);
@@ -38,34 +147,128 @@ fn change_room_index(mut events: EventReader<ChangeRoom>, mut room_index: ResMut
     }
 }
 
-fn move_room_to_camera(
+/// Move the [`FocusedRoom`] marker over to the room being changed to
+fn change_focused_room(
     mut events: EventReader<ChangeRoom>,
-    mut focused: Query<(Entity, &mut Transform, &RoomId), With<FocusedRoom>>,
-    mut rooms: Query<(Entity, &mut Transform, &RoomId, &Room), Without<FocusedRoom>>,
+    focused: Query<Entity, With<FocusedRoom>>,
+    house_graph: Res<HouseGraph>,
     mut commands: Commands,
 ) {
     for event in events.read() {
-        let Ok((old_entity, mut focused_transform, room_id)) = focused.get_single_mut() else {
-            return;
+        let Some(new_entity) = house_graph.entity(event.new_room_id) else {
+            continue;
         };
-        println!(
-            "moved room to: {:?}",
-            Transform::from_translation(Vec3::splat((room_id.0 as f32 + 1.0 as f32) * 1000.0))
-        );
-        *focused_transform =
-            Transform::from_translation(Vec3::splat((room_id.0 as f32 + 1.0 as f32) * 1000.0));
-        commands.entity(old_entity).remove::<FocusedRoom>();
-
-        for (new_entity, mut new_transform, room_id, room) in rooms.iter_mut() {
-            if room_id == &event.new_room_id {
-                println!(
-                    "moved room to: {:?}",
-                    Transform::from_translation(Vec3::splat(0.0))
-                );
-                println!("room_def_id: {:?}", room.room_def_id);
-                *new_transform = Transform::from_translation(Vec3::splat(0.0));
-                commands.entity(new_entity).insert(FocusedRoom);
-            }
+        for old_entity in focused.iter() {
+            commands.entity(old_entity).remove::<FocusedRoom>();
         }
+        commands.entity(new_entity).insert(FocusedRoom);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+    use leafwing_manifest::identifier::Id;
+
+    use super::*;
+    use crate::map::{KeyId, RoomExit};
+
+    /// A room with a door in each of the given directions, each from its own cell and leading to the
+    /// given room
+    fn room_with_doors(doors: &[(RoomConnectionDirection, u32, DoorKind)]) -> Room {
+        let connections = doors
+            .iter()
+            .enumerate()
+            .map(|(index, (direction, room_id, door))| {
+                let exit = RoomExit {
+                    cell: IVec2::new(index as i32, 0),
+                    direction: direction.clone(),
+                };
+                let connection = RoomConnection {
+                    room_id: RoomId(*room_id),
+                    other_exit: exit.clone(),
+                    door: *door,
+                };
+                (exit, connection)
+            })
+            .collect();
+        Room {
+            position: IVec3::ZERO,
+            connections,
+            room_def_id: Id::from_name("ballroom"),
+            keys: Vec::new(),
+            taken_props: Vec::new(),
+        }
+    }
+
+    fn any_door(_door: &DoorKind) -> bool {
+        true
+    }
+
+    #[test]
+    fn every_exit_in_a_direction_gets_taken() {
+        let room = room_with_doors(&[
+            (RoomConnectionDirection::North, 9, DoorKind::Open),
+            (RoomConnectionDirection::North, 5, DoorKind::Open),
+            (RoomConnectionDirection::North, 3, DoorKind::Open),
+            (RoomConnectionDirection::East, 4, DoorKind::Open),
+        ]);
+        let mut exit_cycle = ExitCycle::default();
+        let taken: Vec<RoomId> = (0..6)
+            .map(|_| {
+                exit_cycle
+                    .next_exit(RoomId(2), &room, &RoomConnectionDirection::North, any_door)
+                    .unwrap()
+                    .room_id
+            })
+            .collect();
+        let expected = [RoomId(9), RoomId(5), RoomId(3)];
+        assert_eq!(taken, [expected, expected].concat());
+
+        let east = exit_cycle.next_exit(RoomId(2), &room, &RoomConnectionDirection::East, any_door);
+        assert_eq!(east.map(|connection| connection.room_id), Some(RoomId(4)));
+    }
+
+    #[test]
+    fn doors_that_cant_be_passed_are_skipped() {
+        let room = room_with_doors(&[
+            (RoomConnectionDirection::North, 9, DoorKind::Sealed),
+            (RoomConnectionDirection::North, 5, DoorKind::Open),
+            (
+                RoomConnectionDirection::North,
+                7,
+                DoorKind::Locked(KeyId(0)),
+            ),
+        ]);
+        let held_keys = HeldKeys(HashSet::from([KeyId(0)]));
+        let mut exit_cycle = ExitCycle::default();
+        let taken: HashSet<RoomId> = (0..4)
+            .filter_map(|_| {
+                exit_cycle.next_exit(RoomId(2), &room, &RoomConnectionDirection::North, |door| {
+                    held_keys.can_pass(door)
+                })
+            })
+            .map(|connection| connection.room_id)
+            .collect();
+        assert_eq!(taken, HashSet::from([RoomId(5), RoomId(7)]));
+
+        let no_keys = HeldKeys::default();
+        let mut exit_cycle = ExitCycle::default();
+        for _ in 0..3 {
+            let connection = exit_cycle
+                .next_exit(RoomId(2), &room, &RoomConnectionDirection::North, |door| {
+                    no_keys.can_pass(door)
+                })
+                .unwrap();
+            assert_eq!(connection.room_id, RoomId(5));
+        }
+        let sealed = room_with_doors(&[(RoomConnectionDirection::South, 1, DoorKind::Sealed)]);
+        let south = exit_cycle.next_exit(
+            RoomId(2),
+            &sealed,
+            &RoomConnectionDirection::South,
+            |door| no_keys.can_pass(door),
+        );
+        assert!(south.is_none());
     }
 }
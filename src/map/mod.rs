@@ -4,9 +4,10 @@ use bevy::{
     app::App,
     asset::{Asset, Handle},
     ecs::world::Command,
-    math::Vec3,
+    math::{IVec2, IVec3, Vec2, Vec3},
     prelude::{
-        default, BuildWorldChildren, Component, Entity, Image, Mut, Res, Resource, Transform, World,
+        default, error, BuildWorldChildren, Component, Entity, Image, Mut, ReflectComponent, Res,
+        Resource, Sprite, Transform, Visibility, World,
     },
     reflect::{Reflect, TypePath},
     sprite::SpriteBundle,
@@ -21,63 +22,228 @@ use bevy_asset_loader::{
     standard_dynamic_asset::StandardDynamicAssetCollection,
 };
 use bevy_common_assets::json::JsonAssetPlugin;
+use corruption::{corruption_animation, corruption_color, Corruption};
 use leafwing_manifest::{identifier::Id, manifest::Manifest};
-use manifest::RoomDefinitionManifest;
 use map_navigation::FocusedRoom;
+use props::{prop_bundle, PropDefinition};
+use room_animation::RoomAnimation;
 use serde::{Deserialize, Serialize};
 use toa_animator::ArtCollection;
 
 use crate::AppLoadingState;
 
-pub use generate_map::GenerateMap;
-pub use map_navigation::{ChangeRoom, MapRoomIndex};
+pub use doors::HeldKeys;
+pub use exploration::ExploredRooms;
+pub use generate_map::{GenerateMap, MapGenerationFailed};
+pub use house_graph::HouseGraph;
+pub use house_layout::HouseLayout;
+pub use house_shift::{HouseShifted, ShiftHouse};
+pub use load_house::{current_house_layout, HandMadeHouse, HouseAsset, HouseAssets, LoadHouse};
+pub use manifest::RoomDefinitionManifest;
+pub use map_navigation::{MapRoomIndex, MoveInDirection, RoomTraversed};
+pub use props::{OnInteract, Prop, TakeProp};
 
 mod cleanup_map;
+mod corruption;
+mod doors;
+mod exploration;
 mod generate_map;
+mod house_graph;
+mod house_layout;
+mod house_shift;
+mod load_house;
 mod manifest;
 mod map_navigation;
+mod props;
+mod room_animation;
+mod room_transition;
+mod room_visibility;
 
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HouseSeed>();
+    app.register_type::<House>();
+    app.register_type::<Room>();
+    app.register_type::<RoomId>();
     app.add_plugins((
         generate_map::plugin,
         cleanup_map::plugin,
+        corruption::plugin,
+        doors::plugin,
+        exploration::plugin,
+        house_graph::plugin,
+        house_shift::plugin,
+        load_house::plugin,
         map_navigation::plugin,
+        props::plugin,
         manifest::plugin,
+        room_animation::plugin,
+        room_transition::plugin,
+        room_visibility::plugin,
     ));
 }
 
+/// The size in world units of a single cell of the house grid, which is also the size of a single
+/// cell room's art
+pub const ROOM_CELL_SIZE: Vec2 = Vec2::new(172.0, 128.0);
+
+/// Where a room with the given grid position and footprint sits in the world, centered on its
+/// footprint. Every floor shares the same world space, since only one floor is shown at a time.
+pub fn room_translation(position: IVec3, footprint: &[IVec2]) -> Vec3 {
+    let (min, max) = footprint_bounds(footprint);
+    let center = position.truncate().as_vec2() + (min + max).as_vec2() / 2.0;
+    (center * ROOM_CELL_SIZE).extend(0.0)
+}
+
+/// The size in world units of a room with the given footprint
+pub fn room_size(footprint: &[IVec2]) -> Vec2 {
+    let (min, max) = footprint_bounds(footprint);
+    (max - min + IVec2::ONE).as_vec2() * ROOM_CELL_SIZE
+}
+
+/// The smallest and largest cell of a footprint's bounding box
+fn footprint_bounds(footprint: &[IVec2]) -> (IVec2, IVec2) {
+    let min = footprint
+        .iter()
+        .copied()
+        .reduce(IVec2::min)
+        .unwrap_or_default();
+    let max = footprint
+        .iter()
+        .copied()
+        .reduce(IVec2::max)
+        .unwrap_or_default();
+    (min, max)
+}
+
 /// Unique identifier of a room
-#[derive(Component, Hash, PartialEq, Eq, Clone, Copy, Debug)]
-pub struct RoomId(pub u8);
+#[derive(Component, Reflect, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct RoomId(pub u32);
 
 /// Information on the house
-#[derive(Component)]
+#[derive(Component, Reflect, Serialize, Deserialize, Debug)]
+#[reflect(Component)]
 pub struct House {
     /// All the rooms in the house
     pub rooms: HashMap<RoomId, Entity>,
 }
 
+/// The seed the house was generated from. Generating a house again with this seed will produce the
+/// same layout.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct HouseSeed(pub u64);
+
 /// A room in the house
-#[derive(Component)]
+#[derive(Component, Reflect, Serialize, Deserialize, Debug)]
+#[reflect(Component)]
 pub struct Room {
+    /// The position of the room in the house. `z` is the floor, with the ground floor being `0`
+    pub position: IVec3,
     /// The connections this room has to other rooms
-    pub connections: HashMap<RoomConnectionDirection, RoomId>,
+    pub connections: HashMap<RoomExit, RoomConnection>,
     pub room_def_id: Id<RoomDefinition>,
+    /// Keys lying in this room that the player hasn't picked up yet
+    pub keys: Vec<KeyId>,
+    /// The props that have been taken out of this room, by their place in the room's definition
+    pub taken_props: Vec<u32>,
 }
 
-/// The directions that a room can connect in
+impl Room {
+    /// The connections leaving this room in the given direction, ordered by the cell they leave from
+    pub fn exits(&self, direction: &RoomConnectionDirection) -> Vec<(&RoomExit, &RoomConnection)> {
+        let mut exits: Vec<_> = self
+            .connections
+            .iter()
+            .filter(|(exit, _)| exit.direction == *direction)
+            .collect();
+        exits.sort_by_key(|(exit, _)| (exit.cell.x, exit.cell.y));
+        exits
+    }
+}
+
+/// A spot on the edge of a room where a door can be
 #[derive(Reflect, Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
+pub struct RoomExit {
+    /// The cell of the room's footprint the door is in, relative to the room's position
+    pub cell: IVec2,
+    /// The edge of the cell the door is on
+    pub direction: RoomConnectionDirection,
+}
+
+/// A door leading out of a room into another room
+#[derive(Reflect, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct RoomConnection {
+    /// The room on the other side of the door
+    pub room_id: RoomId,
+    /// The exit of the other room that leads back through this door
+    pub other_exit: RoomExit,
+    pub door: DoorKind,
+}
+
+impl RoomConnection {
+    /// An open door into the given room
+    pub fn open(room_id: RoomId, other_exit: RoomExit) -> Self {
+        RoomConnection {
+            room_id,
+            other_exit,
+            door: DoorKind::Open,
+        }
+    }
+}
+
+/// Whether a door can be walked through
+#[derive(Reflect, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DoorKind {
+    Open,
+    /// Can only be walked through while holding the key with the given id
+    Locked(KeyId),
+    /// Can't be walked through until it is opened with an [`doors::UnsealDoor`] event
+    Sealed,
+}
+
+/// Identifies which locked doors a key opens
+#[derive(
+    Reflect, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug,
+)]
+pub struct KeyId(pub u8);
+
+/// The directions that a room can connect in
+#[derive(Reflect, Deserialize, Serialize, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 pub enum RoomConnectionDirection {
     North,
     East,
     South,
     West,
+    /// Stairs to the floor above
+    Up,
+    /// Stairs to the floor below
+    Down,
 }
 
 #[derive(Asset, Debug, TypePath)]
 pub struct RoomDefinition {
     pub room_name: String,
-    pub allowed_directions: Vec<RoomConnectionDirection>,
+    /// The cells of the grid the room covers, relative to the room's position
+    pub footprint: Vec<IVec2>,
+    /// Every spot a door can be placed on the edges of the room's footprint
+    pub exits: Vec<RoomExit>,
+    /// How likely this room is to be picked during generation compared to the other rooms that fit.
+    pub weight: f32,
+    /// The fewest times this room has to appear in a house.
+    pub min_count: u32,
+    /// The most times this room may appear in a house.
+    pub max_count: Option<u32>,
+    /// The fewest steps away from the entrance this room may be.
+    pub min_depth: u32,
+    /// The most steps away from the entrance this room may be.
+    pub max_depth: Option<u32>,
+    /// The things in the room that can be interacted with
+    pub props: Vec<PropDefinition>,
+    /// How much sanity the player loses on entering this room, on top of what every room costs
+    pub sanity_drain: f32,
+    /// The animation shown at each level of corruption past the first
+    pub corruption_animations: Vec<String>,
     pub art_collection: ArtCollection,
 }
 
@@ -85,45 +251,77 @@ pub struct RoomDefinition {
 pub struct SpawnRoom {
     pub house_entity: Entity,
     pub room_id: RoomId,
-    pub room_connections: HashMap<RoomConnectionDirection, RoomId>,
+    pub room_position: IVec3,
+    pub room_connections: HashMap<RoomExit, RoomConnection>,
     pub room_def_id: String,
+    pub room_keys: Vec<KeyId>,
+    pub room_taken_props: Vec<u32>,
+    pub room_corruption: f32,
 }
 
 impl Command for SpawnRoom {
     fn apply(self, world: &mut bevy::prelude::World) {
         world.resource_scope(
             |world: &mut World, room_assets: Mut<RoomDefinitionManifest>| {
+                let Some(room_definition) = room_assets.get(Id::from_name(&self.room_def_id))
+                else {
+                    error!("There is no room definition named `{}`", self.room_def_id);
+                    return;
+                };
+                let corruption = Corruption::new(self.room_corruption);
+                let textures = &room_definition.art_collection.textures;
+                let animation = Some(corruption_animation(room_definition, corruption.level()))
+                    .filter(|animation| textures.contains_key(*animation))
+                    .unwrap_or(RoomAnimation::IDLE);
+                let Some(texture) = textures.get(animation) else {
+                    error!(
+                        "The room definition `{}` has no `{animation}` texture",
+                        self.room_def_id
+                    );
+                    return;
+                };
                 let room = world
                     .spawn((
                         self.room_id,
                         Room {
+                            position: self.room_position,
                             connections: self.room_connections,
                             room_def_id: Id::from_name(&self.room_def_id),
+                            keys: self.room_keys,
+                            taken_props: self.room_taken_props.clone(),
                         },
+                        RoomAnimation(animation.to_string()),
+                        corruption,
                         SpriteBundle {
-                            transform: Transform::from_translation(Vec3::splat(
-                                (self.room_id.0 as f32 + 1.0) * 1000.0,
+                            sprite: Sprite {
+                                color: corruption_color(corruption.level()),
+                                custom_size: Some(room_size(&room_definition.footprint)),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(room_translation(
+                                self.room_position,
+                                &room_definition.footprint,
                             )),
-                            texture: room_assets
-                                .get(Id::from_name(&self.room_def_id))
-                                .unwrap()
-                                .art_collection
-                                .textures
-                                .get("idle")
-                                .unwrap()
-                                .texture_handle()
-                                .clone(),
+                            texture: texture.texture_handle().clone(),
+                            // Rooms are only shown once they are focused.
+                            visibility: Visibility::Hidden,
                             ..default()
                         },
                     ))
                     .id();
+                let props: Vec<Entity> = room_definition
+                    .props
+                    .iter()
+                    .zip(0..)
+                    .filter(|(_, index)| !self.room_taken_props.contains(index))
+                    .map(|(prop, index)| world.spawn(prop_bundle(prop, index)).id())
+                    .collect();
+                world.entity_mut(room).push_children(&props);
                 if let Some(mut house) = world.entity_mut(self.house_entity).get_mut::<House>() {
                     house.rooms.insert(self.room_id, room);
                 }
-                if self.room_id == RoomId(0) {
-                    world
-                        .entity_mut(room)
-                        .insert((FocusedRoom, Transform::from_translation(Vec3::splat(0.0))));
+                if world.resource::<MapRoomIndex>().0 == self.room_id {
+                    world.entity_mut(room).insert(FocusedRoom);
                 }
                 world.entity_mut(self.house_entity).push_children(&[room]);
             },
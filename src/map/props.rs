@@ -0,0 +1,205 @@
+//! Props are the things inside a room that the player can interact with.
+//!
+//! They are listed in each room's definition and spawned as children of the room. Hovering a prop
+//! with the cursor highlights it, and clicking it triggers [`OnInteract`] on it.
+
+use bevy::{ecs::world::Command, prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::screens::Screen;
+
+use super::{map_navigation::FocusedRoom, room_transition::RoomTransition, Room};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Prop>();
+    app.register_type::<HoveredProp>();
+    app.add_systems(
+        Update,
+        (hover_props, interact_with_props)
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.observe(log_interactions);
+}
+
+/// The tint of a prop under the cursor
+const HIGHLIGHT_COLOR: Color = Color::srgb(1.5, 1.5, 1.2);
+
+/// A prop as it is written in `rooms.assets.json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RawPropDefinition {
+    /// Where the prop is, relative to the center of the room
+    pub position: Vec2,
+    /// The path of the prop's image
+    pub sprite: String,
+    /// The size of the prop's image. Defaults to the size of the image.
+    pub size: Option<Vec2>,
+    /// The size of the area around the prop's position that can be clicked
+    pub hitbox: Vec2,
+    /// What happens when the prop is interacted with
+    pub interaction: String,
+}
+
+/// A prop that is placed in every room of a kind
+#[derive(Debug, Clone)]
+pub struct PropDefinition {
+    pub position: Vec2,
+    pub sprite: Handle<Image>,
+    pub size: Option<Vec2>,
+    pub hitbox: Vec2,
+    pub interaction: String,
+}
+
+impl RawPropDefinition {
+    pub fn load(self, asset_server: &AssetServer) -> PropDefinition {
+        PropDefinition {
+            position: self.position,
+            sprite: asset_server.load(self.sprite),
+            size: self.size,
+            hitbox: self.hitbox,
+            interaction: self.interaction,
+        }
+    }
+}
+
+/// Something in a room that can be interacted with
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Prop {
+    /// What happens when the prop is interacted with
+    pub interaction: String,
+    /// The size of the area around the prop that can be clicked
+    pub hitbox: Vec2,
+    /// The prop's place in its room's definition
+    pub index: u32,
+}
+
+/// Marks the prop under the cursor
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct HoveredProp;
+
+/// Event triggered on a prop when the player clicks it. Observe this event to make a prop do
+/// something.
+#[derive(Event)]
+pub struct OnInteract;
+
+/// The bundle a prop is spawned with, as a child of its room
+pub fn prop_bundle(definition: &PropDefinition, index: u32) -> impl Bundle {
+    (
+        Name::new(format!("Prop: {}", definition.interaction)),
+        Prop {
+            interaction: definition.interaction.clone(),
+            hitbox: definition.hitbox,
+            index,
+        },
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: definition.size,
+                ..default()
+            },
+            // Just in front of the room.
+            transform: Transform::from_translation(definition.position.extend(1.0)),
+            texture: definition.sprite.clone(),
+            ..default()
+        },
+    )
+}
+
+/// Take a prop out of its room. The room remembers it was taken so it stays gone when the house is
+/// loaded again.
+pub struct TakeProp(pub Entity);
+
+impl Command for TakeProp {
+    fn apply(self, world: &mut World) {
+        let Some(index) = world.get::<Prop>(self.0).map(|prop| prop.index) else {
+            return;
+        };
+        let room = world.get::<Parent>(self.0).map(Parent::get);
+        if let Some(mut room) = room.and_then(|room| world.get_mut::<Room>(room)) {
+            room.taken_props.push(index);
+        }
+        world.entity_mut(self.0).despawn_recursive();
+    }
+}
+
+/// Mark and highlight the prop in the focused room that is under the cursor. Nothing is hovered
+/// while the cursor is over the UI or the player is moving between rooms.
+fn hover_props(
+    mut commands: Commands,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    transition: Option<Res<RoomTransition>>,
+    ui_interactions: Query<&Interaction>,
+    focused_rooms: Query<&Children, With<FocusedRoom>>,
+    mut props: Query<(
+        Entity,
+        &Prop,
+        &GlobalTransform,
+        &mut Sprite,
+        Has<HoveredProp>,
+    )>,
+) {
+    let over_ui = ui_interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(Window::cursor_position)
+        .zip(cameras.get_single().ok())
+        .and_then(|(cursor, (camera, camera_transform))| {
+            camera.viewport_to_world_2d(camera_transform, cursor)
+        })
+        .filter(|_| transition.is_none() && !over_ui);
+
+    let hovered = cursor.and_then(|cursor| {
+        let children = focused_rooms.get_single().ok()?;
+        // Props later in the room are drawn on top, so they take the cursor first.
+        props
+            .iter_many(children)
+            .filter(|(_, prop, transform, ..)| {
+                let local = transform
+                    .affine()
+                    .inverse()
+                    .transform_point3(cursor.extend(0.0))
+                    .truncate();
+                local.abs().cmple(prop.hitbox / 2.0).all()
+            })
+            .last()
+            .map(|(entity, ..)| entity)
+    });
+
+    for (entity, _, _, mut sprite, is_hovered) in props.iter_mut() {
+        match (Some(entity) == hovered, is_hovered) {
+            (true, false) => {
+                commands.entity(entity).insert(HoveredProp);
+                sprite.color = HIGHLIGHT_COLOR;
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<HoveredProp>();
+                sprite.color = Color::WHITE;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn interact_with_props(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    hovered: Query<Entity, With<HoveredProp>>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    for entity in hovered.iter() {
+        commands.trigger_targets(OnInteract, entity);
+    }
+}
+
+fn log_interactions(trigger: Trigger<OnInteract>, props: Query<&Prop>) {
+    if let Ok(prop) = props.get(trigger.entity()) {
+        debug!("Interacted with {}", prop.interaction);
+    }
+}
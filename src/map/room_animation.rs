@@ -0,0 +1,152 @@
+//! Which of its animations each room is showing.
+//!
+//! Every room starts out [idle](RoomAnimation::IDLE). Gameplay can send [`SwitchRoomAnimation`] to
+//! show another of the room's animations, which swaps the room over to the texture of the same name
+//! in its definition's `textures`.
+
+use bevy::prelude::*;
+use leafwing_manifest::manifest::Manifest;
+
+use crate::AppSet;
+
+use super::{house_graph::HouseGraph, manifest::RoomDefinitionManifest, Room, RoomId};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RoomAnimation>();
+    app.add_event::<SwitchRoomAnimation>();
+    app.add_systems(
+        Update,
+        switch_room_animations
+            .run_if(on_event::<SwitchRoomAnimation>())
+            .in_set(AppSet::Update),
+    );
+}
+
+/// The animation a room is showing
+#[derive(Component, Reflect, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct RoomAnimation(pub String);
+
+impl RoomAnimation {
+    /// What every room shows when nothing is happening in it
+    pub const IDLE: &'static str = "idle";
+}
+
+impl Default for RoomAnimation {
+    fn default() -> Self {
+        RoomAnimation(RoomAnimation::IDLE.to_string())
+    }
+}
+
+/// Asks a room to show one of its animations. Rooms without the animation keep showing what they
+/// were.
+#[derive(Event, Debug)]
+pub struct SwitchRoomAnimation {
+    pub room_id: RoomId,
+    pub animation: String,
+}
+
+fn switch_room_animations(
+    mut events: EventReader<SwitchRoomAnimation>,
+    house_graph: Res<HouseGraph>,
+    room_assets: Res<RoomDefinitionManifest>,
+    mut rooms: Query<(&Room, &mut RoomAnimation, &mut Handle<Image>)>,
+) {
+    for event in events.read() {
+        let Some((room, mut animation, mut texture)) = house_graph
+            .entity(event.room_id)
+            .and_then(|entity| rooms.get_mut(entity).ok())
+        else {
+            continue;
+        };
+        if animation.0 == event.animation {
+            continue;
+        }
+        let Some(new_texture) = room_assets
+            .get(room.room_def_id)
+            .and_then(|definition| definition.art_collection.textures.get(&event.animation))
+        else {
+            debug!(
+                "Room {:?} has no {} animation",
+                event.room_id, event.animation
+            );
+            continue;
+        };
+        *texture = new_texture.texture_handle().clone();
+        animation.0.clone_from(&event.animation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, utils::HashMap};
+    use leafwing_manifest::identifier::Id;
+
+    use super::*;
+    use crate::map::manifest::RawRoomDefinitionManifest;
+
+    /// An app with the shipped room definitions and a lone entrance in it
+    fn app_with_entrance() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.init_asset::<Image>();
+        app.init_asset::<TextureAtlasLayout>();
+        app.add_event::<SwitchRoomAnimation>();
+        let raw_manifest: RawRoomDefinitionManifest =
+            serde_json::from_str(include_str!("../../assets/rooms.assets.json")).unwrap();
+        let manifest =
+            RoomDefinitionManifest::from_raw_manifest(raw_manifest, app.world_mut()).unwrap();
+        app.insert_resource(manifest);
+
+        let world = app.world_mut();
+        let entrance = world
+            .spawn((
+                RoomId(0),
+                Room {
+                    position: IVec3::ZERO,
+                    connections: HashMap::new(),
+                    room_def_id: Id::from_name("entrance"),
+                    keys: Vec::new(),
+                    taken_props: Vec::new(),
+                },
+                RoomAnimation::default(),
+                Handle::<Image>::default(),
+            ))
+            .id();
+        let house_graph = HouseGraph::new([(RoomId(0), entrance, &HashMap::new())]);
+        world.insert_resource(house_graph);
+        (app, entrance)
+    }
+
+    fn switch(app: &mut App, animation: &str) {
+        let world = app.world_mut();
+        world.send_event(SwitchRoomAnimation {
+            room_id: RoomId(0),
+            animation: animation.to_string(),
+        });
+        world.run_system_once(switch_room_animations);
+    }
+
+    #[test]
+    fn switching_changes_the_animation_shown() {
+        let (mut app, entrance) = app_with_entrance();
+        switch(&mut app, "disturbed");
+        assert_eq!(
+            app.world().get::<RoomAnimation>(entrance),
+            Some(&RoomAnimation("disturbed".to_string()))
+        );
+
+        // The entrance has no such animation, so it keeps the one it has.
+        switch(&mut app, "on-fire");
+        assert_eq!(
+            app.world().get::<RoomAnimation>(entrance),
+            Some(&RoomAnimation("disturbed".to_string()))
+        );
+
+        switch(&mut app, RoomAnimation::IDLE);
+        assert_eq!(
+            app.world().get::<RoomAnimation>(entrance),
+            Some(&RoomAnimation::default())
+        );
+    }
+}
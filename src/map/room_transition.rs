@@ -0,0 +1,323 @@
+//! Animates moving from one room to another.
+//!
+//! A transition starts whenever the [`FocusedRoom`] moves to another room. While it runs, a
+//! [`RoomTransition`] resource exists and navigation input is ignored.
+
+use std::time::Duration;
+
+use bevy::{ecs::removal_detection::RemovedComponents, prelude::*};
+
+use crate::{screens::Screen, AppSet};
+
+use super::{map_navigation::FocusedRoom, Room, RoomId};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RoomTransitionSettings>();
+    app.init_resource::<RoomTransitionSettings>();
+    app.add_event::<RoomEntered>();
+    app.add_event::<RoomExited>();
+
+    app.add_systems(
+        Update,
+        (
+            tick_room_transition
+                .in_set(AppSet::TickTimers)
+                .run_if(resource_exists::<RoomTransition>),
+            (
+                start_room_transition.run_if(focused_room_changed),
+                (animate_room_transition, finish_room_transition)
+                    .chain()
+                    .run_if(resource_exists::<RoomTransition>),
+            )
+                .chain()
+                .in_set(AppSet::Update)
+                .in_set(RoomTransitionSystems),
+        ),
+    );
+    app.add_systems(
+        Update,
+        log_room_changes.run_if(on_event::<RoomExited>().or_else(on_event::<RoomEntered>())),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), |mut commands: Commands| {
+        commands.remove_resource::<RoomTransition>();
+    });
+}
+
+/// The systems that start, play and finish room transitions
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct RoomTransitionSystems;
+
+/// How moving between rooms looks
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionEffect {
+    /// The camera slides over to the new room, in the direction the player is going. Taking the
+    /// stairs fades instead, since the floors above and below share the same spot on screen.
+    Slide,
+    /// The screen fades to black and back in on the new room
+    Fade,
+    /// The walls of the old room heave in and out before the new room does the same
+    Breathe,
+}
+
+/// Configures how moving between rooms looks
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+pub struct RoomTransitionSettings {
+    pub effect: TransitionEffect,
+    pub duration: Duration,
+}
+
+impl Default for RoomTransitionSettings {
+    fn default() -> Self {
+        RoomTransitionSettings {
+            effect: TransitionEffect::Slide,
+            duration: Duration::from_millis(400),
+        }
+    }
+}
+
+/// A transition between two rooms that is currently playing
+#[derive(Resource, Debug)]
+pub struct RoomTransition {
+    pub to: RoomId,
+    from_entity: Entity,
+    to_entity: Entity,
+    from_translation: Vec3,
+    to_translation: Vec3,
+    effect: TransitionEffect,
+    timer: Timer,
+}
+
+impl RoomTransition {
+    /// How far along the transition is, from `0.0` to `1.0`
+    fn progress(&self) -> f32 {
+        self.timer.fraction()
+    }
+}
+
+/// Sent when the player starts leaving a room
+#[derive(Event, Debug)]
+pub struct RoomExited {
+    pub room_id: RoomId,
+}
+
+/// Sent once the player has arrived in a room and any transition into it has finished
+#[derive(Event, Debug)]
+pub struct RoomEntered {
+    pub room_id: RoomId,
+}
+
+/// Covers the screen while fading between rooms
+#[derive(Component)]
+struct TransitionOverlay;
+
+/// How far the walls move out when breathing, relative to the room's size
+const BREATHE_AMOUNT: f32 = 0.06;
+
+fn focused_room_changed(focused: Query<(), Added<FocusedRoom>>) -> bool {
+    !focused.is_empty()
+}
+
+fn tick_room_transition(time: Res<Time>, mut transition: ResMut<RoomTransition>) {
+    transition.timer.tick(time.delta());
+}
+
+fn start_room_transition(
+    mut commands: Commands,
+    settings: Res<RoomTransitionSettings>,
+    running: Option<Res<RoomTransition>>,
+    focused: Query<Entity, Added<FocusedRoom>>,
+    mut unfocused: RemovedComponents<FocusedRoom>,
+    mut rooms: Query<(&RoomId, &Room, &mut Transform, &mut Visibility)>,
+    overlays: Query<Entity, With<TransitionOverlay>>,
+    mut room_exited: EventWriter<RoomExited>,
+    mut room_entered: EventWriter<RoomEntered>,
+) {
+    let Ok(to_entity) = focused.get_single() else {
+        return;
+    };
+    let from_entity = unfocused
+        .read()
+        .filter(|entity| *entity != to_entity && rooms.contains(*entity))
+        .last();
+
+    // A new room was focused before the last transition finished, so drop that one where it is.
+    if let Some(running) = running {
+        for entity in [running.from_entity, running.to_entity] {
+            if let Ok((_, _, mut transform, _)) = rooms.get_mut(entity) {
+                transform.scale = Vec3::ONE;
+            }
+        }
+        for overlay in overlays.iter() {
+            commands.entity(overlay).despawn_recursive();
+        }
+        commands.remove_resource::<RoomTransition>();
+    }
+
+    let Ok((&to, to_room, to_transform, _)) = rooms.get(to_entity) else {
+        return;
+    };
+    let to_floor = to_room.position.z;
+    let to_translation = to_transform.translation;
+    // Without a room to come from, such as when the house was just spawned, there's nothing to
+    // transition from.
+    let Some((from_entity, (&from, from_room, from_transform, _))) =
+        from_entity.and_then(|entity| Some((entity, rooms.get(entity).ok()?)))
+    else {
+        room_entered.send(RoomEntered { room_id: to });
+        return;
+    };
+    let from_translation = from_transform.translation;
+    let effect = match settings.effect {
+        TransitionEffect::Slide if from_room.position.z != to_floor => TransitionEffect::Fade,
+        effect => effect,
+    };
+
+    // The room being left stays shown until the transition is over.
+    if let Ok((_, _, _, mut to_visibility)) = rooms.get_mut(to_entity) {
+        *to_visibility = match effect {
+            TransitionEffect::Slide => Visibility::Inherited,
+            TransitionEffect::Fade | TransitionEffect::Breathe => Visibility::Hidden,
+        };
+    }
+    if effect == TransitionEffect::Fade {
+        commands.spawn((
+            Name::new("Room transition overlay"),
+            TransitionOverlay,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::BLACK.with_alpha(0.0)),
+                z_index: ZIndex::Global(i32::MAX),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+
+    room_exited.send(RoomExited { room_id: from });
+    commands.insert_resource(RoomTransition {
+        to,
+        from_entity,
+        to_entity,
+        from_translation,
+        to_translation,
+        effect,
+        timer: Timer::new(settings.duration, TimerMode::Once),
+    });
+}
+
+fn animate_room_transition(
+    transition: Res<RoomTransition>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+    mut rooms: Query<(&mut Transform, &mut Visibility), Without<Camera>>,
+    mut overlays: Query<&mut BackgroundColor, With<TransitionOverlay>>,
+) {
+    let progress = transition.progress();
+    let camera_translation = match transition.effect {
+        TransitionEffect::Slide => {
+            let eased = progress * progress * (3.0 - 2.0 * progress);
+            transition
+                .from_translation
+                .lerp(transition.to_translation, eased)
+        }
+        // The other effects hide the cut over to the new room halfway through.
+        TransitionEffect::Fade | TransitionEffect::Breathe => {
+            if progress < 0.5 {
+                transition.from_translation
+            } else {
+                transition.to_translation
+            }
+        }
+    };
+    for mut camera_transform in cameras.iter_mut() {
+        camera_transform.translation.x = camera_translation.x;
+        camera_transform.translation.y = camera_translation.y;
+    }
+
+    match transition.effect {
+        TransitionEffect::Slide => {}
+        TransitionEffect::Fade => {
+            let alpha = 1.0 - (2.0 * progress - 1.0).abs();
+            for mut background in overlays.iter_mut() {
+                background.0.set_alpha(alpha);
+            }
+            swap_rooms_halfway(&transition, &mut rooms);
+        }
+        TransitionEffect::Breathe => {
+            let breath = (progress * std::f32::consts::TAU * 2.0).sin().abs() * BREATHE_AMOUNT;
+            let breathing_room = if progress < 0.5 {
+                transition.from_entity
+            } else {
+                transition.to_entity
+            };
+            if let Ok((mut transform, _)) = rooms.get_mut(breathing_room) {
+                transform.scale = Vec3::new(1.0 + breath, 1.0 + breath, 1.0);
+            }
+            swap_rooms_halfway(&transition, &mut rooms);
+        }
+    }
+}
+
+/// Show the new room in place of the old one once the transition is halfway done
+fn swap_rooms_halfway(
+    transition: &RoomTransition,
+    rooms: &mut Query<(&mut Transform, &mut Visibility), Without<Camera>>,
+) {
+    let halfway = transition.progress() >= 0.5;
+    if let Ok((_, mut visibility)) = rooms.get_mut(transition.from_entity) {
+        visibility.set_if_neq(if halfway {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+    }
+    if let Ok((_, mut visibility)) = rooms.get_mut(transition.to_entity) {
+        visibility.set_if_neq(if halfway {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+fn finish_room_transition(
+    mut commands: Commands,
+    transition: Res<RoomTransition>,
+    mut rooms: Query<&mut Transform, Without<Camera>>,
+    overlays: Query<Entity, With<TransitionOverlay>>,
+    mut room_entered: EventWriter<RoomEntered>,
+) {
+    if !transition.timer.finished() {
+        return;
+    }
+
+    for entity in [transition.from_entity, transition.to_entity] {
+        if let Ok(mut transform) = rooms.get_mut(entity) {
+            transform.scale = Vec3::ONE;
+        }
+    }
+    for overlay in overlays.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+    commands.remove_resource::<RoomTransition>();
+    room_entered.send(RoomEntered {
+        room_id: transition.to,
+    });
+}
+
+fn log_room_changes(
+    mut room_exited: EventReader<RoomExited>,
+    mut room_entered: EventReader<RoomEntered>,
+) {
+    for event in room_exited.read() {
+        debug!("Left room {:?}", event.room_id);
+    }
+    for event in room_entered.read() {
+        debug!("Entered room {:?}", event.room_id);
+    }
+}
@@ -0,0 +1,54 @@
+//! Shows the focused room and points the camera at it.
+//!
+//! Every room stays at its real place in the world, so which rooms are shown is only a matter of
+//! their [`Visibility`]. While moving between rooms the transition takes care of both, so this only
+//! settles things once the player has arrived.
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+use super::{
+    map_navigation::FocusedRoom,
+    room_transition::{RoomEntered, RoomTransitionSystems},
+    Room,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (show_focused_room, move_camera_to_focused_room)
+            .run_if(on_event::<RoomEntered>())
+            .after(RoomTransitionSystems)
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Show the focused room and hide every other one
+fn show_focused_room(mut rooms: Query<(&mut Visibility, Has<FocusedRoom>), With<Room>>) {
+    for (mut visibility, focused) in rooms.iter_mut() {
+        let new_visibility = if focused {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        // Only write when it changes so that change detection only picks up the rooms that did.
+        visibility.set_if_neq(new_visibility);
+    }
+}
+
+/// Rooms are children of the house, which sits at the origin, so a room's [`Transform`] is already
+/// where it is in the world. It is used over the [`GlobalTransform`] since that isn't filled in
+/// until the end of the frame a room is spawned in.
+fn move_camera_to_focused_room(
+    focused: Query<&Transform, (With<FocusedRoom>, Without<Camera>)>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+) {
+    let Ok(room_transform) = focused.get_single() else {
+        return;
+    };
+    for mut camera_transform in cameras.iter_mut() {
+        camera_transform.translation.x = room_transform.translation.x;
+        camera_transform.translation.y = room_transform.translation.y;
+    }
+}
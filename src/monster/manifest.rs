@@ -0,0 +1,106 @@
+use bevy::{prelude::*, utils::HashMap};
+use leafwing_manifest::{
+    identifier::Id,
+    manifest::{Manifest, ManifestFormat},
+    plugin::RegisterManifest,
+};
+use serde::{Deserialize, Serialize};
+
+use super::MonsterBehaviour;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_manifest::<MonsterManifest>("monsters.assets.json");
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RawMonsterDefinition {
+    /// The name the monster is referred to by, such as in a saved run.
+    pub monster_name: String,
+    /// The name the player sees.
+    pub display_name: String,
+    pub behaviour: MonsterBehaviour,
+    /// How many turns pass between each of the monster's moves.
+    #[serde(default = "default_moves_every")]
+    pub moves_every: u32,
+    /// How much sanity the player loses when the monster finds them.
+    pub encounter_sanity: f32,
+    /// How many of the monster roam each house.
+    #[serde(default = "default_count")]
+    pub count: u32,
+    /// The fewest steps away from the entrance the monster may start.
+    #[serde(default)]
+    pub min_depth: u32,
+}
+
+fn default_moves_every() -> u32 {
+    1
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// Something that roams the house
+#[derive(Debug, Clone, TypePath)]
+pub struct MonsterDefinition {
+    pub monster_name: String,
+    pub display_name: String,
+    pub behaviour: MonsterBehaviour,
+    /// How many turns pass between each of the monster's moves
+    pub moves_every: u32,
+    /// How much sanity the player loses when the monster finds them
+    pub encounter_sanity: f32,
+    /// How many of the monster roam each house
+    pub count: u32,
+    /// The fewest steps away from the entrance the monster may start
+    pub min_depth: u32,
+}
+
+#[derive(Debug, Resource)]
+pub struct MonsterManifest {
+    pub monsters: HashMap<Id<MonsterDefinition>, MonsterDefinition>,
+}
+#[derive(Debug, Resource, Serialize, Deserialize, Asset, TypePath)]
+pub struct RawMonsterManifest {
+    items: Vec<RawMonsterDefinition>,
+}
+impl Manifest for MonsterManifest {
+    type RawManifest = RawMonsterManifest;
+
+    type RawItem = RawMonsterDefinition;
+
+    type Item = MonsterDefinition;
+
+    type ConversionError = std::convert::Infallible;
+
+    const FORMAT: ManifestFormat = ManifestFormat::Json;
+
+    fn from_raw_manifest(
+        raw_manifest: Self::RawManifest,
+        _world: &mut World,
+    ) -> Result<Self, Self::ConversionError> {
+        let monsters: HashMap<_, _> = raw_manifest
+            .items
+            .into_iter()
+            .map(|raw_monster| {
+                let monster = MonsterDefinition {
+                    monster_name: raw_monster.monster_name,
+                    display_name: raw_monster.display_name,
+                    behaviour: raw_monster.behaviour,
+                    // A monster can't move more than once a turn.
+                    moves_every: raw_monster.moves_every.max(1),
+                    encounter_sanity: raw_monster.encounter_sanity,
+                    count: raw_monster.count,
+                    min_depth: raw_monster.min_depth,
+                };
+                (Id::from_name(&monster.monster_name), monster)
+            })
+            .collect();
+
+        Ok(MonsterManifest { monsters })
+    }
+
+    fn get(&self, id: Id<Self::Item>) -> Option<&Self::Item> {
+        self.monsters.get(&id)
+    }
+}
@@ -0,0 +1,453 @@
+//! The monsters that roam the house.
+//!
+//! Monsters are defined in `monsters.assets.json`. Each one is in a room of the house and moves
+//! through its doors as turns end, picking where to go by its [`MonsterBehaviour`]. Monsters can get
+//! through any door that isn't sealed. When one ends up in the same room as the player a
+//! [`MonsterEncounter`] is sent: the player loses sanity and the monster retreats to the far side of
+//! the house. Monsters in a room next to the player can be heard moving.
+
+mod manifest;
+
+use bevy::{ecs::world::Command, prelude::*};
+use bevy_asset_loader::{
+    asset_collection::AssetCollection,
+    loading_state::{
+        config::{ConfigureLoadingState, LoadingStateConfig},
+        LoadingStateAppExt,
+    },
+};
+use leafwing_manifest::{identifier::Id, manifest::Manifest};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::SoundEffect,
+    map::{DoorKind, House, HouseGraph, MapRoomIndex, Room, RoomId},
+    sanity::Sanity,
+    screens::Screen,
+    turns::{turn_ended, TurnEnded},
+    AppLoadingState, AppSet,
+};
+
+use manifest::MonsterDefinition;
+
+pub use manifest::MonsterManifest;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(manifest::plugin);
+    app.register_type::<Monster>();
+    app.add_event::<MonsterEncounter>();
+    app.configure_loading_state(
+        LoadingStateConfig::new(AppLoadingState::Loading).load_collection::<MonsterAssets>(),
+    );
+
+    app.add_systems(
+        Update,
+        (
+            move_monsters.run_if(turn_ended),
+            find_player.run_if(monsters_moved.or_else(resource_changed::<MapRoomIndex>)),
+            (scare_player, drive_monsters_away).run_if(on_event::<MonsterEncounter>()),
+            play_nearby_footsteps.run_if(turn_ended),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// How many rooms away the player has to come before an ambushing monster goes for them
+const AMBUSH_RANGE: usize = 2;
+
+/// A monster roaming the house
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct Monster {
+    pub monster_def_id: Id<MonsterDefinition>,
+    /// The room the monster is in
+    pub room_id: RoomId,
+}
+
+/// How a monster picks which room to move to
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MonsterBehaviour {
+    /// Wanders into a random neighbouring room
+    RandomWalk,
+    /// Heads for the player by the shortest way there
+    Hunt,
+    /// Waits where it is until the player comes close, then goes for them
+    Ambush,
+}
+
+impl MonsterBehaviour {
+    /// The room a monster with this behaviour moves to next from `from`, which is `from` itself if
+    /// it stays where it is
+    pub fn next_room(
+        &self,
+        from: RoomId,
+        player_room: RoomId,
+        house_graph: &HouseGraph,
+        rng: &mut impl Rng,
+    ) -> RoomId {
+        let towards_player = || {
+            house_graph
+                .shortest_path(from, player_room, monster_can_pass)
+                .filter(|path| *self == MonsterBehaviour::Hunt || path.len() <= AMBUSH_RANGE + 1)
+                .and_then(|path| path.get(1).copied())
+        };
+        match self {
+            MonsterBehaviour::RandomWalk => {
+                let neighbours: Vec<RoomId> = house_graph
+                    .connections(from)
+                    .filter(|(_, connection)| monster_can_pass(&connection.door))
+                    .map(|(_, connection)| connection.room_id)
+                    .collect();
+                neighbours.choose(rng).copied()
+            }
+            MonsterBehaviour::Hunt | MonsterBehaviour::Ambush => towards_player(),
+        }
+        .unwrap_or(from)
+    }
+}
+
+/// Monsters get through every door, unless it is sealed
+fn monster_can_pass(door: &DoorKind) -> bool {
+    *door != DoorKind::Sealed
+}
+
+/// Sent when a monster and the player end up in the same room
+#[derive(Event, Debug)]
+pub struct MonsterEncounter {
+    pub monster: Entity,
+    pub room_id: RoomId,
+}
+
+/// Run condition for when any monster has moved or been placed
+pub fn monsters_moved(monsters: Query<(), Changed<Monster>>) -> bool {
+    !monsters.is_empty()
+}
+
+/// Put monsters in the spawned house. Monsters from a saved run go back to the rooms they were in,
+/// otherwise every monster in the [`MonsterManifest`] starts in a random room far enough from the
+/// entrance. Ambushers start in a dead end if there is one far enough in.
+pub struct PlaceMonsters {
+    /// The name of each monster in a saved run, along with the room it was in
+    pub saved: Option<Vec<(String, RoomId)>>,
+}
+
+impl Command for PlaceMonsters {
+    fn apply(self, world: &mut World) {
+        // There is nothing to place monsters in if the house couldn't be spawned.
+        if world.query::<&House>().get_single(world).is_err() {
+            return;
+        }
+        let placements = match self.saved {
+            Some(saved) => saved,
+            None => new_monster_placements(world),
+        };
+        for (monster_name, room_id) in placements {
+            if !world.resource::<HouseGraph>().contains(room_id) {
+                continue;
+            }
+            world.spawn((
+                Name::new(format!("Monster: {monster_name}")),
+                Monster {
+                    monster_def_id: Id::from_name(&monster_name),
+                    room_id,
+                },
+                StateScoped(Screen::Gameplay),
+            ));
+        }
+    }
+}
+
+/// Pick a room for each monster of a new run
+fn new_monster_placements(world: &mut World) -> Vec<(String, RoomId)> {
+    let rooms: Vec<RoomId> = world
+        .query_filtered::<&RoomId, With<Room>>()
+        .iter(world)
+        .filter(|room_id| room_id.0 != 0)
+        .copied()
+        .collect();
+    let Some(monster_assets) = world.get_resource::<MonsterManifest>() else {
+        return Vec::new();
+    };
+    let house_graph = world.resource::<HouseGraph>();
+    let dead_ends = house_graph.dead_ends();
+    let mut definitions: Vec<&MonsterDefinition> = monster_assets.monsters.values().collect();
+    definitions.sort_by(|a, b| a.monster_name.cmp(&b.monster_name));
+
+    let mut rng = rand::thread_rng();
+    let mut placements = Vec::new();
+    for definition in definitions {
+        let deep_enough: Vec<RoomId> = rooms
+            .iter()
+            .filter(|room_id| {
+                house_graph
+                    .distance_from_entrance(**room_id)
+                    .is_some_and(|depth| depth >= definition.min_depth)
+                    // A monster shut in behind sealed doors would never get to the player.
+                    && house_graph.is_reachable(**room_id, RoomId(0), monster_can_pass)
+            })
+            .copied()
+            .collect();
+        let lairs: Vec<RoomId> = match definition.behaviour {
+            MonsterBehaviour::Ambush => deep_enough
+                .iter()
+                .filter(|room_id| dead_ends.contains(room_id))
+                .copied()
+                .collect(),
+            MonsterBehaviour::RandomWalk | MonsterBehaviour::Hunt => Vec::new(),
+        };
+        // Small houses may not go deep enough, in which case anywhere but the entrance will do.
+        let candidates = if !lairs.is_empty() {
+            &lairs
+        } else if !deep_enough.is_empty() {
+            &deep_enough
+        } else {
+            &rooms
+        };
+        for _ in 0..definition.count {
+            if let Some(room_id) = candidates.choose(&mut rng) {
+                placements.push((definition.monster_name.clone(), *room_id));
+            }
+        }
+    }
+    placements
+}
+
+#[derive(Resource, AssetCollection, Reflect, Clone)]
+pub struct MonsterAssets {
+    #[asset(
+        paths(
+            "audio/sound_effects/step1.ogg",
+            "audio/sound_effects/step2.ogg",
+            "audio/sound_effects/step3.ogg",
+            "audio/sound_effects/step4.ogg"
+        ),
+        collection(typed)
+    )]
+    footsteps: Vec<Handle<AudioSource>>,
+}
+
+fn move_monsters(
+    mut events: EventReader<TurnEnded>,
+    map_room: Res<MapRoomIndex>,
+    house_graph: Res<HouseGraph>,
+    monster_assets: Res<MonsterManifest>,
+    mut monsters: Query<&mut Monster>,
+) {
+    let mut rng = rand::thread_rng();
+    for event in events.read() {
+        for mut monster in monsters.iter_mut() {
+            let Some(definition) = monster_assets.get(monster.monster_def_id) else {
+                continue;
+            };
+            if event.turn % definition.moves_every != 0 {
+                continue;
+            }
+            let next_room =
+                definition
+                    .behaviour
+                    .next_room(monster.room_id, map_room.0, &house_graph, &mut rng);
+            if next_room != monster.room_id {
+                monster.room_id = next_room;
+            }
+        }
+    }
+}
+
+fn find_player(
+    map_room: Res<MapRoomIndex>,
+    monsters: Query<(Entity, &Monster)>,
+    mut encounter_writer: EventWriter<MonsterEncounter>,
+) {
+    for (entity, monster) in monsters.iter() {
+        if monster.room_id == map_room.0 {
+            encounter_writer.send(MonsterEncounter {
+                monster: entity,
+                room_id: monster.room_id,
+            });
+        }
+    }
+}
+
+fn scare_player(
+    mut events: EventReader<MonsterEncounter>,
+    mut sanity: ResMut<Sanity>,
+    monster_assets: Res<MonsterManifest>,
+    monsters: Query<&Monster>,
+) {
+    for event in events.read() {
+        let Some(definition) = monsters
+            .get(event.monster)
+            .ok()
+            .and_then(|monster| monster_assets.get(monster.monster_def_id))
+        else {
+            continue;
+        };
+        info!(
+            "{} found the player in room {:?}",
+            definition.display_name, event.room_id
+        );
+        sanity.drain(definition.encounter_sanity);
+    }
+}
+
+/// Send monsters that found the player to the room furthest from them, so they aren't found again
+/// straight away
+fn drive_monsters_away(
+    mut events: EventReader<MonsterEncounter>,
+    map_room: Res<MapRoomIndex>,
+    house_graph: Res<HouseGraph>,
+    mut monsters: Query<&mut Monster>,
+) {
+    for event in events.read() {
+        let Ok(mut monster) = monsters.get_mut(event.monster) else {
+            continue;
+        };
+        // A monster with nowhere to go is left untouched, as a changed monster would be found in the
+        // player's room again straight away.
+        if let Some(room_id) = furthest_room(&house_graph, monster.room_id, map_room.0) {
+            monster.room_id = room_id;
+        }
+    }
+}
+
+/// The room a monster in `from` can get to that is the furthest from the player, or `None` if it
+/// can't get away from the player or from where it is, such as when both are shut in the same room
+fn furthest_room(house_graph: &HouseGraph, from: RoomId, player_room: RoomId) -> Option<RoomId> {
+    house_graph
+        .reachable_from(from, monster_can_pass)
+        .into_iter()
+        .filter(|room_id| *room_id != from && *room_id != player_room)
+        .filter_map(|room_id| {
+            let path = house_graph.shortest_path(player_room, room_id, monster_can_pass)?;
+            Some((path.len(), room_id.0))
+        })
+        .max()
+        .map(|(_, room_id)| RoomId(room_id))
+}
+
+/// Let the player hear monsters in the rooms next to theirs
+fn play_nearby_footsteps(
+    mut commands: Commands,
+    map_room: Res<MapRoomIndex>,
+    house_graph: Res<HouseGraph>,
+    monster_assets: Res<MonsterAssets>,
+    monsters: Query<&Monster>,
+) {
+    let neighbours = house_graph.neighbours(map_room.0);
+    if !monsters
+        .iter()
+        .any(|monster| neighbours.contains(&monster.room_id))
+    {
+        return;
+    }
+    let Some(source) = monster_assets.footsteps.choose(&mut rand::thread_rng()) else {
+        return;
+    };
+    commands.spawn((
+        AudioBundle {
+            source: source.clone(),
+            settings: PlaybackSettings::DESPAWN,
+        },
+        SoundEffect,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::map::KeyId;
+
+    /// 0 - 1 - 2 - 3 - 4
+    ///     |
+    ///     5
+    fn hallway(door: DoorKind) -> HouseGraph {
+        HouseGraph::from_doors(
+            6,
+            &[
+                (0, 1, DoorKind::Open),
+                (1, 2, DoorKind::Open),
+                (2, 3, DoorKind::Open),
+                (3, 4, DoorKind::Open),
+                (1, 5, door),
+            ],
+        )
+    }
+
+    #[test]
+    fn random_walks_only_go_through_passable_doors() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let graph = hallway(DoorKind::Sealed);
+        for _ in 0..100 {
+            let next =
+                MonsterBehaviour::RandomWalk.next_room(RoomId(1), RoomId(4), &graph, &mut rng);
+            assert!(next == RoomId(0) || next == RoomId(2));
+        }
+
+        // Locked doors don't stop monsters.
+        let graph = hallway(DoorKind::Locked(KeyId(0)));
+        let next_rooms: Vec<RoomId> = (0..100)
+            .map(|_| MonsterBehaviour::RandomWalk.next_room(RoomId(1), RoomId(4), &graph, &mut rng))
+            .collect();
+        assert!(next_rooms.contains(&RoomId(5)));
+    }
+
+    #[test]
+    fn hunters_follow_the_shortest_path() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let graph = hallway(DoorKind::Open);
+        let mut room = RoomId(5);
+        let mut steps = Vec::new();
+        while room != RoomId(4) {
+            room = MonsterBehaviour::Hunt.next_room(room, RoomId(4), &graph, &mut rng);
+            steps.push(room);
+        }
+        assert_eq!(steps, [RoomId(1), RoomId(2), RoomId(3), RoomId(4)]);
+    }
+
+    #[test]
+    fn ambushers_wait_until_the_player_is_in_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let graph = hallway(DoorKind::Open);
+        let next = MonsterBehaviour::Ambush.next_room(RoomId(4), RoomId(0), &graph, &mut rng);
+        assert_eq!(next, RoomId(4));
+
+        // The player is AMBUSH_RANGE rooms away.
+        let next = MonsterBehaviour::Ambush.next_room(RoomId(4), RoomId(2), &graph, &mut rng);
+        assert_eq!(next, RoomId(3));
+    }
+
+    #[test]
+    fn sealed_doors_stop_monsters() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let graph = hallway(DoorKind::Sealed);
+        for behaviour in [MonsterBehaviour::Hunt, MonsterBehaviour::Ambush] {
+            let next = behaviour.next_room(RoomId(5), RoomId(1), &graph, &mut rng);
+            assert_eq!(next, RoomId(5));
+        }
+        assert_eq!(furthest_room(&graph, RoomId(5), RoomId(1)), None);
+        assert_eq!(furthest_room(&graph, RoomId(1), RoomId(4)), Some(RoomId(0)));
+    }
+
+    #[test]
+    fn monsters_are_driven_to_the_far_side_of_the_house() {
+        let graph = hallway(DoorKind::Open);
+        assert_eq!(furthest_room(&graph, RoomId(0), RoomId(0)), Some(RoomId(4)));
+        assert_eq!(furthest_room(&graph, RoomId(5), RoomId(5)), Some(RoomId(4)));
+    }
+
+    #[test]
+    fn monsters_walled_in_with_the_player_stay_put() {
+        let graph = hallway(DoorKind::Sealed);
+        assert_eq!(furthest_room(&graph, RoomId(5), RoomId(5)), None);
+
+        // 0 - 1 # 2, where # is a sealed door
+        let graph = HouseGraph::from_doors(3, &[(0, 1, DoorKind::Open), (1, 2, DoorKind::Sealed)]);
+        assert_eq!(furthest_room(&graph, RoomId(0), RoomId(1)), None);
+        assert_eq!(furthest_room(&graph, RoomId(1), RoomId(1)), Some(RoomId(0)));
+    }
+}
@@ -0,0 +1,231 @@
+//! The player's sanity, which the house wears down as they move through it.
+//!
+//! Every room the player walks into costs some sanity, and rooms can cost more through their
+//! `sanity_drain` in `rooms.assets.json`. Items with a `sanity` value restore it when used. As
+//! sanity falls through each [`SanityLevel`] the game distorts what the player sees and hears, and a
+//! [`SanityLevelChanged`] is sent. Each time it gets worse the house shifts around them, and
+//! when it runs out the run is over.
+
+use bevy::prelude::*;
+use leafwing_manifest::manifest::Manifest;
+
+use crate::{
+    audio::Music,
+    inventory::{Inventory, ItemManifest, UseItem},
+    map::{HouseGraph, Room, RoomDefinitionManifest, RoomTraversed, ShiftHouse},
+    screens::Screen,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Sanity>();
+    app.init_resource::<Sanity>();
+    app.init_resource::<CurrentSanityLevel>();
+    app.add_systems(OnEnter(Screen::Gameplay), |mut commands: Commands| {
+        commands.insert_resource(Sanity::default());
+        commands.insert_resource(CurrentSanityLevel::default());
+    });
+    app.add_event::<SanityLevelChanged>();
+
+    app.add_systems(
+        Update,
+        (
+            drain_sanity.run_if(on_event::<RoomTraversed>()),
+            use_sanity_items.run_if(on_event::<UseItem>()),
+            (track_sanity_level, lose_mind).run_if(resource_changed::<Sanity>),
+            distort_music.run_if(resource_changed::<CurrentSanityLevel>),
+            shift_house_on_breakdown.run_if(on_event::<SanityLevelChanged>()),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// How much sanity the player starts a run with, and the most they can have
+const MAX_SANITY: f32 = 100.0;
+/// How much sanity walking into any room costs
+const TRAVERSAL_DRAIN: f32 = 2.0;
+
+/// How much the house has gotten to the player
+#[derive(Resource, Reflect, Debug, Clone, Copy)]
+#[reflect(Resource)]
+pub struct Sanity {
+    current: f32,
+    max: f32,
+}
+
+impl Default for Sanity {
+    fn default() -> Self {
+        Sanity {
+            current: MAX_SANITY,
+            max: MAX_SANITY,
+        }
+    }
+}
+
+impl Sanity {
+    /// The given amount of sanity, out of the most the player can have
+    pub fn new(current: f32) -> Self {
+        Sanity {
+            current: current.clamp(0.0, MAX_SANITY),
+            max: MAX_SANITY,
+        }
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// How much sanity is left, from `0.0` to `1.0`
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+
+    pub fn drain(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.0);
+    }
+
+    pub fn restore(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+
+    /// Whether the player has no sanity left, which ends the run
+    pub fn is_lost(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    pub fn level(&self) -> SanityLevel {
+        match self.fraction() {
+            fraction if fraction > 0.75 => SanityLevel::Lucid,
+            fraction if fraction > 0.5 => SanityLevel::Uneasy,
+            fraction if fraction > 0.25 => SanityLevel::Shaken,
+            _ => SanityLevel::Unravelling,
+        }
+    }
+}
+
+/// How far gone the player is, by quarters of their sanity
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SanityLevel {
+    Lucid,
+    Uneasy,
+    Shaken,
+    Unravelling,
+}
+
+/// The [`SanityLevel`] the game is showing, or `None` before it has been worked out for this run
+#[derive(Resource, Debug, Default)]
+pub struct CurrentSanityLevel(pub Option<SanityLevel>);
+
+impl CurrentSanityLevel {
+    /// The level to show, which is [`SanityLevel::Lucid`] until it has been worked out
+    pub fn level(&self) -> SanityLevel {
+        self.0.unwrap_or(SanityLevel::Lucid)
+    }
+}
+
+/// Sent when the player's [`SanityLevel`] changes, including when a run starts. Listen for this to
+/// react to the player losing their mind.
+#[derive(Event, Debug)]
+pub struct SanityLevelChanged {
+    pub level: SanityLevel,
+    /// The level before this change, or `None` when the run just started
+    pub previous: Option<SanityLevel>,
+}
+
+/// Drain sanity for every room the player walks into, more so in rooms that ask for it
+fn drain_sanity(
+    mut events: EventReader<RoomTraversed>,
+    mut sanity: ResMut<Sanity>,
+    house_graph: Res<HouseGraph>,
+    rooms: Query<&Room>,
+    room_assets: Res<RoomDefinitionManifest>,
+) {
+    for event in events.read() {
+        let room_drain = house_graph
+            .entity(event.to)
+            .and_then(|entity| rooms.get(entity).ok())
+            .and_then(|room| room_assets.get(room.room_def_id))
+            .map_or(0.0, |definition| definition.sanity_drain);
+        sanity.drain(TRAVERSAL_DRAIN + room_drain);
+    }
+}
+
+/// Use up items that restore sanity
+fn use_sanity_items(
+    mut events: EventReader<UseItem>,
+    mut sanity: ResMut<Sanity>,
+    mut inventory: ResMut<Inventory>,
+    item_manifest: Res<ItemManifest>,
+) {
+    for event in events.read() {
+        let Some(definition) = item_manifest.get(event.item) else {
+            continue;
+        };
+        if definition.sanity <= 0.0 {
+            continue;
+        }
+        if !inventory.has(event.item) {
+            warn!(
+                "Tried to use {} without carrying any",
+                definition.display_name
+            );
+            continue;
+        }
+        inventory.remove(event.item, 1);
+        sanity.restore(definition.sanity);
+    }
+}
+
+fn track_sanity_level(
+    sanity: Res<Sanity>,
+    mut current_level: ResMut<CurrentSanityLevel>,
+    mut level_writer: EventWriter<SanityLevelChanged>,
+) {
+    let level = sanity.level();
+    if current_level.0 != Some(level) {
+        let previous = current_level.0.replace(level);
+        level_writer.send(SanityLevelChanged { level, previous });
+    }
+}
+
+fn lose_mind(sanity: Res<Sanity>, mut next_screen: ResMut<NextState<Screen>>) {
+    if sanity.is_lost() {
+        next_screen.set(Screen::GameOver);
+    }
+}
+
+/// Slow the music down as the player unravels
+fn distort_music(current_level: Res<CurrentSanityLevel>, music: Query<&AudioSink, With<Music>>) {
+    let speed = match current_level.level() {
+        SanityLevel::Lucid => 1.0,
+        SanityLevel::Uneasy => 0.95,
+        SanityLevel::Shaken => 0.88,
+        SanityLevel::Unravelling => 0.75,
+    };
+    for sink in music.iter() {
+        sink.set_speed(speed);
+    }
+}
+
+/// The worse the player's sanity gets, the more the house moves around them
+fn shift_house_on_breakdown(
+    mut events: EventReader<SanityLevelChanged>,
+    mut shift_writer: EventWriter<ShiftHouse>,
+) {
+    for event in events.read() {
+        if event
+            .previous
+            .is_some_and(|previous| event.level > previous)
+        {
+            let swaps = match event.level {
+                SanityLevel::Lucid => 0,
+                SanityLevel::Uneasy => 1,
+                SanityLevel::Shaken => 2,
+                SanityLevel::Unravelling => 3,
+            };
+            shift_writer.send(ShiftHouse { swaps });
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Brings saves written by older versions of the game up to date.
+
+use serde_json::Value;
+
+use super::SaveError;
+
+/// The version of the save format this version of the game writes.
+pub const CURRENT_VERSION: u32 = 1;
+
+/// The migration at index `n` turns a version `n + 1` save into a version `n + 2` save. Add one here
+/// whenever [`SavedRun`](super::SavedRun) changes in a way older saves can't be read as.
+const MIGRATIONS: &[fn(&mut Value)] = &[];
+
+const _: () = assert!(MIGRATIONS.len() as u32 == CURRENT_VERSION - 1);
+
+/// Run every migration from the save's version up to [`CURRENT_VERSION`] on it.
+pub fn migrate(save: &mut Value) -> Result<(), SaveError> {
+    run_migrations(save, MIGRATIONS)
+}
+
+/// Bring a save up to the version that comes after the given migrations, following the same rules
+/// as [`MIGRATIONS`].
+fn run_migrations(save: &mut Value, migrations: &[fn(&mut Value)]) -> Result<(), SaveError> {
+    let current_version = migrations.len() as u32 + 1;
+    let version = save
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or(SaveError::MissingVersion)? as u32;
+    if version == 0 || version > current_version {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+
+    for migration in &migrations[version as usize - 1..] {
+        migration(save);
+    }
+    save["version"] = current_version.into();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Version 1 saves called the current room `room`.
+    fn rename_room(save: &mut Value) {
+        if let Some(room) = save.as_object_mut().and_then(|save| save.remove("room")) {
+            save["current_room"] = room;
+        }
+    }
+
+    /// Version 2 saves didn't have any keys.
+    fn add_keys(save: &mut Value) {
+        save["held_keys"] = json!([]);
+    }
+
+    const SAMPLE_MIGRATIONS: &[fn(&mut Value)] = &[rename_room, add_keys];
+
+    #[test]
+    fn old_saves_run_every_later_migration() {
+        let mut save = json!({ "version": 1, "room": 4 });
+        run_migrations(&mut save, SAMPLE_MIGRATIONS).unwrap();
+        assert_eq!(
+            save,
+            json!({ "version": 3, "current_room": 4, "held_keys": [] })
+        );
+
+        let mut save = json!({ "version": 2, "current_room": 4 });
+        run_migrations(&mut save, SAMPLE_MIGRATIONS).unwrap();
+        assert_eq!(
+            save,
+            json!({ "version": 3, "current_room": 4, "held_keys": [] })
+        );
+    }
+
+    #[test]
+    fn current_saves_are_left_alone() {
+        let mut save = json!({ "version": 3, "current_room": 4, "held_keys": [1] });
+        let before = save.clone();
+        run_migrations(&mut save, SAMPLE_MIGRATIONS).unwrap();
+        assert_eq!(save, before);
+
+        let mut save = json!({ "version": CURRENT_VERSION });
+        let before = save.clone();
+        migrate(&mut save).unwrap();
+        assert_eq!(save, before);
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        for version in [0, 4, 100] {
+            let mut save = json!({ "version": version });
+            assert!(matches!(
+                run_migrations(&mut save, SAMPLE_MIGRATIONS),
+                Err(SaveError::UnsupportedVersion(rejected)) if rejected == version
+            ));
+        }
+        let mut save = json!({ "version": CURRENT_VERSION + 1 });
+        assert!(matches!(
+            migrate(&mut save),
+            Err(SaveError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn saves_without_a_version_are_rejected() {
+        let mut save = json!({ "current_room": 4 });
+        assert!(matches!(migrate(&mut save), Err(SaveError::MissingVersion)));
+    }
+}
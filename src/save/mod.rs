@@ -0,0 +1,298 @@
+//! Saves the current run as it is played, so it can be continued from the title screen.
+
+mod migrate;
+pub(crate) mod storage;
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use leafwing_manifest::{identifier::Id, manifest::Manifest};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    inventory::{Inventory, ItemManifest},
+    map::{
+        current_house_layout, ExploredRooms, HeldKeys, HouseLayout, KeyId, MapRoomIndex, Room,
+        RoomId,
+    },
+    monster::{monsters_moved, Monster, MonsterManifest},
+    sanity::{CurrentSanityLevel, Sanity},
+    screens::Screen,
+    turns::GameClock,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            // Saving while a run is being resumed would overwrite it with the fresh run state.
+            save_run.run_if(
+                not(resource_exists::<ResumeRun>).and_then(
+                    resource_changed::<MapRoomIndex>
+                        .or_else(resource_changed::<Inventory>)
+                        .or_else(resource_changed::<Sanity>)
+                        .or_else(resource_changed::<GameClock>)
+                        .or_else(monsters_moved)
+                        .or_else(input_just_pressed(KeyCode::Escape)),
+                ),
+            ),
+            finish_resuming_run.run_if(resource_exists::<ResumeRun>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Everything needed to continue a run where it was left off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedRun {
+    /// The version of the save format, used to bring older saves up to date.
+    pub version: u32,
+    pub house: HouseLayout,
+    /// The room the player is in.
+    pub current_room: RoomId,
+    pub visited_rooms: Vec<RoomId>,
+    /// Rooms seen through a doorway but not visited. Saves from before these were tracked rebuild
+    /// them from the visited rooms.
+    #[serde(default)]
+    pub discovered_rooms: Vec<RoomId>,
+    pub held_keys: Vec<KeyId>,
+    /// The items the player is carrying, by name, along with how many of each.
+    #[serde(default)]
+    pub items: Vec<(String, u32)>,
+    /// How much sanity the player has left. Saves from before sanity was tracked start with full
+    /// sanity.
+    #[serde(default)]
+    pub sanity: Option<f32>,
+    /// How many turns have been taken and what time it is in the house. Saves from before turns
+    /// were counted start from the first turn.
+    #[serde(default)]
+    pub clock: GameClock,
+    /// The name of each monster in the house, along with the room it is in. Saves from before
+    /// monsters roamed the house get new ones.
+    #[serde(default)]
+    pub monsters: Option<Vec<(String, RoomId)>>,
+}
+
+/// The saved run to continue when entering [`Screen::Gameplay`]. Without it a new house is
+/// generated.
+#[derive(Resource, Debug)]
+pub struct ResumeRun(pub SavedRun);
+
+/// What the saved run is stored under.
+const SAVE_NAME: &str = "save";
+
+/// Why a saved run or the settings could not be read or written.
+#[derive(Debug)]
+pub enum SaveError {
+    /// The platform's storage could not be used.
+    Storage(String),
+    /// The save is not valid JSON or doesn't describe a run.
+    Format(serde_json::Error),
+    /// The save doesn't say which version of the save format it uses.
+    MissingVersion,
+    /// The save was written by a newer version of the game, or is otherwise of a version this one
+    /// can't read.
+    UnsupportedVersion(u32),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Storage(error) => write!(f, "could not access storage: {error}"),
+            SaveError::Format(error) => write!(f, "the stored data is malformed: {error}"),
+            SaveError::MissingVersion => write!(f, "the save has no version"),
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "save version {version} is not supported")
+            }
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> Self {
+        SaveError::Format(error)
+    }
+}
+
+/// Read the saved run, bringing it up to date if it was written by an older version of the game.
+///
+/// Returns `None` if there is no saved run.
+pub fn load_run() -> Result<Option<SavedRun>, SaveError> {
+    let Some(json) = storage::read(SAVE_NAME)? else {
+        return Ok(None);
+    };
+    let mut save: serde_json::Value = serde_json::from_str(&json)?;
+    migrate::migrate(&mut save)?;
+    Ok(Some(serde_json::from_value(save)?))
+}
+
+/// Whether there is a saved run that can be continued.
+pub fn has_saved_run() -> bool {
+    matches!(load_run(), Ok(Some(_)))
+}
+
+/// Throw away the saved run, such as when it has been lost.
+pub fn delete_run() -> Result<(), SaveError> {
+    storage::remove(SAVE_NAME)
+}
+
+fn write_run(run: &SavedRun) -> Result<(), SaveError> {
+    storage::write(SAVE_NAME, &serde_json::to_string(run)?)
+}
+
+fn save_run(world: &mut World) {
+    let Some(house) = current_house_layout(world) else {
+        return;
+    };
+    let explored_rooms = world.resource::<ExploredRooms>();
+    let visited_rooms = explored_rooms.visited();
+    let discovered_rooms = explored_rooms
+        .discovered()
+        .into_iter()
+        .filter(|room_id| !explored_rooms.is_visited(*room_id))
+        .collect();
+    let mut held_keys: Vec<KeyId> = world.resource::<HeldKeys>().0.iter().copied().collect();
+    held_keys.sort();
+    let Some(item_manifest) = world.get_resource::<ItemManifest>() else {
+        return;
+    };
+    let items = world
+        .resource::<Inventory>()
+        .items()
+        .filter_map(|(item, count)| Some((item_manifest.get(item)?.item_name.clone(), count)))
+        .collect();
+    let monsters: Vec<Monster> = world.query::<&Monster>().iter(world).cloned().collect();
+    let Some(monster_assets) = world.get_resource::<MonsterManifest>() else {
+        return;
+    };
+    let mut monsters: Vec<(String, RoomId)> = monsters
+        .into_iter()
+        .filter_map(|monster| {
+            let definition = monster_assets.get(monster.monster_def_id)?;
+            Some((definition.monster_name.clone(), monster.room_id))
+        })
+        .collect();
+    monsters.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let run = SavedRun {
+        version: migrate::CURRENT_VERSION,
+        house,
+        current_room: world.resource::<MapRoomIndex>().0,
+        visited_rooms,
+        discovered_rooms,
+        held_keys,
+        items,
+        sanity: Some(world.resource::<Sanity>().current()),
+        clock: *world.resource::<GameClock>(),
+        monsters: Some(monsters),
+    };
+    if let Err(error) = write_run(&run) {
+        error!("Failed to save the run: {error}");
+    }
+}
+
+/// Restore everything about the resumed run that isn't part of the house or the room the player is
+/// in, which were already set up from it when entering [`Screen::Gameplay`].
+fn finish_resuming_run(
+    mut commands: Commands,
+    resume: Res<ResumeRun>,
+    mut held_keys: ResMut<HeldKeys>,
+    mut inventory: ResMut<Inventory>,
+    mut sanity: ResMut<Sanity>,
+    mut current_level: ResMut<CurrentSanityLevel>,
+    mut clock: ResMut<GameClock>,
+    mut explored_rooms: ResMut<ExploredRooms>,
+    rooms: Query<(&RoomId, &Room)>,
+) {
+    let run = &resume.0;
+    held_keys.0 = run.held_keys.iter().copied().collect();
+    for (item_name, count) in &run.items {
+        inventory.add(Id::from_name(item_name), *count);
+    }
+    if let Some(saved_sanity) = run.sanity {
+        *sanity = Sanity::new(saved_sanity);
+    }
+    // Picking up where the run left off isn't the player's sanity getting worse, so the house
+    // shouldn't shift for it.
+    current_level.0 = Some(sanity.level());
+    *clock = run.clock;
+    for (room_id, room) in rooms.iter() {
+        if run.visited_rooms.contains(room_id) {
+            explored_rooms.visit(*room_id, room);
+        }
+    }
+    for room_id in &run.discovered_rooms {
+        explored_rooms.discover(*room_id);
+    }
+    commands.remove_resource::<ResumeRun>();
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn saved_run() -> SavedRun {
+        SavedRun {
+            version: migrate::CURRENT_VERSION,
+            house: HouseLayout::default(),
+            current_room: RoomId(3),
+            visited_rooms: vec![RoomId(0), RoomId(3)],
+            discovered_rooms: vec![RoomId(1)],
+            held_keys: vec![KeyId(0)],
+            items: vec![("rubber-duck".to_string(), 2)],
+            sanity: Some(42.0),
+            clock: GameClock::default(),
+            monsters: Some(vec![("hound".to_string(), RoomId(5))]),
+        }
+    }
+
+    #[test]
+    fn saved_runs_survive_serde() {
+        let run = saved_run();
+        let json = serde_json::to_string(&run).unwrap();
+        let read: SavedRun = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            serde_json::to_value(read).unwrap(),
+            serde_json::to_value(run).unwrap()
+        );
+    }
+
+    #[test]
+    fn saved_runs_survive_storage() {
+        let run = saved_run();
+        write_run(&run).unwrap();
+        let read = load_run().unwrap().unwrap();
+        delete_run().unwrap();
+        assert_eq!(
+            serde_json::to_value(read).unwrap(),
+            serde_json::to_value(run).unwrap()
+        );
+        assert!(load_run().unwrap().is_none());
+    }
+
+    #[test]
+    fn first_saves_fill_in_what_they_didnt_track() {
+        let mut save = json!({
+            "version": 1,
+            "house": { "rooms": [] },
+            "current_room": 0,
+            "visited_rooms": [0],
+            "held_keys": [],
+        });
+        migrate::migrate(&mut save).unwrap();
+        let run: SavedRun = serde_json::from_value(save).unwrap();
+        assert!(run.discovered_rooms.is_empty());
+        assert!(run.items.is_empty());
+        assert_eq!(run.sanity, None);
+        assert_eq!(run.clock.turn(), 0);
+        assert_eq!(run.monsters, None);
+    }
+}
@@ -0,0 +1,126 @@
+//! Where the save and settings are kept: files in the platform's data directory on native, and
+//! `localStorage` on the web. Each thing stored is a string under its own name.
+
+#[cfg(not(target_family = "wasm"))]
+pub use native::{read, remove, write};
+#[cfg(target_family = "wasm")]
+pub use web::{read, remove, write};
+
+#[cfg(not(target_family = "wasm"))]
+mod native {
+    use std::{env, fs, io::ErrorKind, path::PathBuf};
+
+    use crate::save::SaveError;
+
+    /// Read what is stored under `name`, or `None` if there isn't anything.
+    pub fn read(name: &str) -> Result<Option<String>, SaveError> {
+        match fs::read_to_string(file_path(name)?) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(SaveError::Storage(error.to_string())),
+        }
+    }
+
+    /// Replace what is stored under `name` with the given contents.
+    pub fn write(name: &str, contents: &str) -> Result<(), SaveError> {
+        let path = file_path(name)?;
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| SaveError::Storage(error.to_string()))?;
+        }
+        fs::write(path, contents).map_err(|error| SaveError::Storage(error.to_string()))
+    }
+
+    /// Remove what is stored under `name`, if there is anything.
+    pub fn remove(name: &str) -> Result<(), SaveError> {
+        match fs::remove_file(file_path(name)?) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(SaveError::Storage(error.to_string())),
+        }
+    }
+
+    fn file_path(name: &str) -> Result<PathBuf, SaveError> {
+        data_dir()
+            .map(|directory| {
+                directory
+                    .join("eldritch-house")
+                    .join(format!("{name}.json"))
+            })
+            .ok_or_else(|| SaveError::Storage("could not find a data directory".to_string()))
+    }
+
+    /// Tests keep what they store out of the player's real data directory, and out of each other's
+    /// way. Each test runs on its own thread, so each thread gets its own directory.
+    #[cfg(test)]
+    fn data_dir() -> Option<PathBuf> {
+        use std::{
+            process,
+            sync::atomic::{AtomicU32, Ordering},
+        };
+
+        static NEXT_DIRECTORY: AtomicU32 = AtomicU32::new(0);
+        thread_local! {
+            static DIRECTORY: PathBuf = env::temp_dir().join(format!(
+                "eldritch-house-test-{}-{}",
+                process::id(),
+                NEXT_DIRECTORY.fetch_add(1, Ordering::Relaxed)
+            ));
+        }
+        Some(DIRECTORY.with(PathBuf::clone))
+    }
+
+    #[cfg(all(not(test), target_os = "windows"))]
+    fn data_dir() -> Option<PathBuf> {
+        env::var_os("APPDATA").map(PathBuf::from)
+    }
+
+    #[cfg(all(not(test), target_os = "macos"))]
+    fn data_dir() -> Option<PathBuf> {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    }
+
+    #[cfg(all(not(test), not(any(target_os = "windows", target_os = "macos"))))]
+    fn data_dir() -> Option<PathBuf> {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod web {
+    use web_sys::Storage;
+
+    use crate::save::SaveError;
+
+    /// Read what is stored under `name`, or `None` if there isn't anything.
+    pub fn read(name: &str) -> Result<Option<String>, SaveError> {
+        local_storage()?
+            .get_item(&storage_key(name))
+            .map_err(|error| SaveError::Storage(format!("{error:?}")))
+    }
+
+    /// Replace what is stored under `name` with the given contents.
+    pub fn write(name: &str, contents: &str) -> Result<(), SaveError> {
+        local_storage()?
+            .set_item(&storage_key(name), contents)
+            .map_err(|error| SaveError::Storage(format!("{error:?}")))
+    }
+
+    /// Remove what is stored under `name`, if there is anything.
+    pub fn remove(name: &str) -> Result<(), SaveError> {
+        local_storage()?
+            .remove_item(&storage_key(name))
+            .map_err(|error| SaveError::Storage(format!("{error:?}")))
+    }
+
+    fn storage_key(name: &str) -> String {
+        format!("eldritch-house-{name}")
+    }
+
+    fn local_storage() -> Result<Storage, SaveError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| SaveError::Storage("localStorage is not available".to_string()))
+    }
+}
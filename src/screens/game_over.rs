@@ -0,0 +1,39 @@
+//! The screen shown when the player has lost their mind to the house.
+
+use bevy::prelude::*;
+
+use crate::{save::delete_run, screens::Screen, theme::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(Screen::GameOver),
+        (forget_run, spawn_game_over_screen),
+    );
+}
+
+/// A lost run can't be continued.
+fn forget_run() {
+    if let Err(error) = delete_run() {
+        error!("Failed to delete the lost run: {error}");
+    }
+}
+
+fn spawn_game_over_screen(mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::GameOver))
+        .with_children(|children| {
+            children.header("Your Mind Is Lost");
+            children.label("The house has taken what was left of you.");
+            children.button("Try Again").observe(enter_gameplay_screen);
+            children.button("Title Screen").observe(enter_title_screen);
+        });
+}
+
+fn enter_gameplay_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Gameplay);
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -1,7 +1,8 @@
-use crate::map::{ChangeRoom, MapRoomIndex, Room, RoomConnectionDirection, RoomId};
+use crate::map::{HouseGraph, MapRoomIndex, MoveInDirection, Room, RoomConnectionDirection};
 use crate::screens::Screen;
 use crate::theme::prelude::OnPress;
 use crate::theme::widgets::{Containers, Widgets};
+use crate::turns::GameClock;
 use bevy::prelude::Val::Px;
 use bevy::prelude::*;
 
@@ -9,7 +10,13 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Gameplay), setup_gameplay_ui);
     app.add_systems(
         Update,
-        enable_disable_move_room_buttons.run_if(resource_changed::<MapRoomIndex>),
+        (enable_disable_move_room_buttons, update_floor_label)
+            .run_if(resource_changed::<MapRoomIndex>),
+    );
+    app.add_systems(
+        Update,
+        update_clock_label
+            .run_if(in_state(Screen::Gameplay).and_then(resource_changed::<GameClock>)),
     );
 }
 
@@ -18,6 +25,35 @@ fn setup_gameplay_ui(mut commands: Commands) {
         .ui_root()
         .insert(StateScoped(Screen::Gameplay))
         .with_children(|children| {
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Px(25.0),
+                        left: Px(25.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    children.label("").insert(FloorLabel);
+                });
+
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        // Just below the sanity meter.
+                        top: Px(90.0),
+                        left: Px(25.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    children.label("").insert(ClockLabel);
+                });
+
             children
                 .spawn(NodeBundle {
                     style: Style {
@@ -80,6 +116,38 @@ fn setup_gameplay_ui(mut commands: Commands) {
                         .insert(MoveRoomButton(RoomConnectionDirection::East))
                         .observe(move_room_button);
                 });
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Px(25.0),
+                        right: Px(25.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    children
+                        .button("Go Up")
+                        .insert(MoveRoomButton(RoomConnectionDirection::Up))
+                        .observe(move_room_button);
+                });
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        bottom: Px(25.0),
+                        right: Px(25.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    children
+                        .button("Go Down")
+                        .insert(MoveRoomButton(RoomConnectionDirection::Down))
+                        .observe(move_room_button);
+                });
         });
 }
 
@@ -89,39 +157,77 @@ struct MoveRoomButton(RoomConnectionDirection);
 
 fn move_room_button(
     trigger: Trigger<OnPress>,
-    mut event_writer: EventWriter<ChangeRoom>,
-    room_res: Res<MapRoomIndex>,
+    mut event_writer: EventWriter<MoveInDirection>,
     buttons: Query<&MoveRoomButton>,
-    rooms: Query<(&RoomId, &Room)>,
 ) {
     let Ok(move_room_button) = buttons.get(trigger.entity()) else {
         return;
     };
-
-    let Some((_room_id, room)) = rooms.iter().find(|(id, _)| **id == room_res.0) else {
-        return;
-    };
-    let Some(target_room_id) = room.connections.get(&move_room_button.0) else {
-        return;
-    };
-    event_writer.send(ChangeRoom {
-        new_room_id: target_room_id.clone(),
+    event_writer.send(MoveInDirection {
+        direction: move_room_button.0.clone(),
     });
 }
 
 fn enable_disable_move_room_buttons(
     room_res: Res<MapRoomIndex>,
     mut buttons: Query<(&MoveRoomButton, &mut Style)>,
-    rooms: Query<(&RoomId, &Room)>,
+    house_graph: Res<HouseGraph>,
+    rooms: Query<&Room>,
 ) {
-    let Some((_room_id, room)) = rooms.iter().find(|(id, _)| **id == room_res.0) else {
+    let Some(room) = house_graph
+        .entity(room_res.0)
+        .and_then(|entity| rooms.get(entity).ok())
+    else {
         return;
     };
     for (move_room_button, mut style) in buttons.iter_mut() {
-        if !room.connections.contains_key(&move_room_button.0) {
+        if room.exits(&move_room_button.0).is_empty() {
             style.display = Display::None;
         } else {
             style.display = Display::Flex;
         }
     }
 }
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct FloorLabel;
+
+fn update_floor_label(
+    room_res: Res<MapRoomIndex>,
+    mut labels: Query<&mut Text, With<FloorLabel>>,
+    house_graph: Res<HouseGraph>,
+    rooms: Query<&Room>,
+) {
+    let Some(room) = house_graph
+        .entity(room_res.0)
+        .and_then(|entity| rooms.get(entity).ok())
+    else {
+        return;
+    };
+    let floor_name = floor_name(room.position.z);
+    for mut text in labels.iter_mut() {
+        text.sections[0].value.clone_from(&floor_name);
+    }
+}
+
+/// What the player calls the given floor of the house
+pub(super) fn floor_name(floor: i32) -> String {
+    match floor {
+        0 => "Ground Floor".to_string(),
+        -1 => "Cellar".to_string(),
+        floor if floor > 0 => format!("Floor {floor}"),
+        floor => format!("Cellar {}", -floor),
+    }
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct ClockLabel;
+
+fn update_clock_label(clock: Res<GameClock>, mut labels: Query<&mut Text, With<ClockLabel>>) {
+    let (hour, minute) = clock.time_of_day();
+    for mut text in labels.iter_mut() {
+        text.sections[0].value = format!("Turn {}  {hour:02}:{minute:02}", clock.turn() + 1);
+    }
+}
@@ -0,0 +1,206 @@
+//! A panel listing the items the player is carrying, opened with a button or the I key.
+
+use bevy::{
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    ui::{FocusPolicy, Val::*},
+};
+use leafwing_manifest::{identifier::Id, manifest::Manifest};
+
+use crate::{
+    inventory::{Inventory, InventoryChanged, ItemDefinition, ItemManifest, UseItem},
+    screens::Screen,
+    theme::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_inventory_button);
+    app.add_event::<ToggleInventory>();
+    app.add_systems(
+        Update,
+        record_inventory_input
+            .run_if(in_state(Screen::Gameplay).and_then(input_just_pressed(KeyCode::KeyI)))
+            .in_set(AppSet::RecordInput),
+    );
+    app.add_systems(
+        Update,
+        (
+            toggle_inventory.run_if(on_event::<ToggleInventory>()),
+            draw_inventory.run_if(on_event::<InventoryChanged>().or_else(inventory_opened)),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSet::Update),
+    );
+}
+
+const PANEL_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
+/// The size of an item's icon in the panel
+const ICON_SIZE: f32 = 48.0;
+
+/// Opens the inventory panel, or closes it if it is open
+#[derive(Event, Debug)]
+struct ToggleInventory;
+
+/// The inventory panel
+#[derive(Component)]
+struct InventoryRoot;
+
+/// The area of the inventory panel that items are listed in
+#[derive(Component)]
+struct InventoryItems;
+
+/// A button that uses up one of an item
+#[derive(Component)]
+struct UseItemButton(Id<ItemDefinition>);
+
+fn spawn_inventory_button(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Inventory Button"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    // Just right of the minimap.
+                    left: Px(250.0),
+                    bottom: Px(25.0),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|children| {
+            children.button("Inventory").observe(press_toggle_inventory);
+        });
+}
+
+fn press_toggle_inventory(
+    _trigger: Trigger<OnPress>,
+    mut toggle_writer: EventWriter<ToggleInventory>,
+) {
+    toggle_writer.send(ToggleInventory);
+}
+
+fn record_inventory_input(mut toggle_writer: EventWriter<ToggleInventory>) {
+    toggle_writer.send(ToggleInventory);
+}
+
+fn toggle_inventory(
+    mut commands: Commands,
+    mut events: EventReader<ToggleInventory>,
+    roots: Query<Entity, With<InventoryRoot>>,
+) {
+    // Pressing I while clicking the button still only toggles the panel once.
+    events.clear();
+
+    if !roots.is_empty() {
+        for root in roots.iter() {
+            commands.entity(root).despawn_recursive();
+        }
+        return;
+    }
+
+    commands
+        .ui_root()
+        .insert((
+            Name::new("Inventory"),
+            InventoryRoot,
+            BackgroundColor(PANEL_BACKGROUND),
+            // Keep clicks from reaching the gameplay buttons and props underneath.
+            FocusPolicy::Block,
+            Interaction::None,
+            ZIndex::Global(1),
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|children| {
+            children.header("Inventory");
+            children.spawn((
+                Name::new("Inventory Items"),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                InventoryItems,
+            ));
+            children.button("Close").observe(press_toggle_inventory);
+        });
+}
+
+fn inventory_opened(items: Query<(), Added<InventoryItems>>) -> bool {
+    !items.is_empty()
+}
+
+/// List the items being carried, with their icons and descriptions
+fn draw_inventory(
+    mut commands: Commands,
+    inventory: Res<Inventory>,
+    item_manifest: Res<ItemManifest>,
+    lists: Query<Entity, With<InventoryItems>>,
+) {
+    for list in lists.iter() {
+        commands
+            .entity(list)
+            .despawn_descendants()
+            .with_children(|children| {
+                if inventory.items().next().is_none() {
+                    children.label("You aren't carrying anything.");
+                    return;
+                }
+                for (item, count) in inventory.items() {
+                    let Some(definition) = item_manifest.get(item) else {
+                        continue;
+                    };
+                    children
+                        .spawn((
+                            Name::new(format!("Item: {}", definition.item_name)),
+                            NodeBundle {
+                                style: Style {
+                                    align_items: AlignItems::Center,
+                                    column_gap: Px(10.0),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                        ))
+                        .with_children(|children| {
+                            children.spawn(ImageBundle {
+                                style: Style {
+                                    width: Px(ICON_SIZE),
+                                    height: Px(ICON_SIZE),
+                                    ..default()
+                                },
+                                image: UiImage::new(definition.icon.clone()),
+                                ..default()
+                            });
+                            children.label(format!(
+                                "{} x{count}: {}",
+                                definition.display_name, definition.description
+                            ));
+                            if definition.sanity > 0.0 {
+                                children
+                                    .button("Use")
+                                    .insert(UseItemButton(item))
+                                    .observe(use_item);
+                            }
+                        });
+                }
+            });
+    }
+}
+
+fn use_item(
+    trigger: Trigger<OnPress>,
+    buttons: Query<&UseItemButton>,
+    mut use_item_writer: EventWriter<UseItem>,
+) {
+    let Ok(button) = buttons.get(trigger.entity()) else {
+        return;
+    };
+    use_item_writer.send(UseItem { item: button.0 });
+}
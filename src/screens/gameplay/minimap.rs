@@ -0,0 +1,542 @@
+//! The minimap in the corner of the screen, and the full map of the house that opens from it.
+//!
+//! Both show the rooms the player has visited on the floor being looked at, along with the doors
+//! leading out of them. Rooms that have only been seen through a doorway are shown faintly.
+
+use bevy::{
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    ui::{FocusPolicy, Val::*},
+};
+
+use crate::{
+    map::{
+        ExploredRooms, House, HouseGraph, HouseShifted, MapRoomIndex, Room,
+        RoomConnectionDirection, RoomDefinitionManifest, RoomId,
+    },
+    screens::Screen,
+    theme::prelude::*,
+    AppSet,
+};
+
+use super::gameplay_ui::floor_name;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_minimap);
+    app.add_systems(OnExit(Screen::Gameplay), |mut commands: Commands| {
+        commands.remove_resource::<FullMap>();
+    });
+    app.add_event::<ToggleFullMap>();
+    app.add_systems(
+        Update,
+        record_full_map_input
+            .run_if(in_state(Screen::Gameplay).and_then(input_just_pressed(KeyCode::KeyM)))
+            .in_set(AppSet::RecordInput),
+    );
+    app.add_systems(
+        Update,
+        (
+            toggle_full_map.run_if(on_event::<ToggleFullMap>()),
+            draw_minimap.run_if(
+                resource_changed::<MapRoomIndex>
+                    .or_else(resource_changed::<ExploredRooms>)
+                    .or_else(on_event::<HouseShifted>())
+                    .or_else(rooms_changed),
+            ),
+            draw_full_map.run_if(
+                resource_exists::<FullMap>.and_then(
+                    resource_changed::<FullMap>
+                        .or_else(resource_changed::<MapRoomIndex>)
+                        .or_else(resource_changed::<ExploredRooms>)
+                        .or_else(on_event::<HouseShifted>())
+                        .or_else(rooms_changed),
+                ),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// The size of a grid cell on the minimap
+const MINIMAP_CELL: Vec2 = Vec2::new(20.0, 15.0);
+/// The size of the minimap
+const MINIMAP_SIZE: Vec2 = Vec2::new(200.0, 150.0);
+/// The size of a grid cell on the full map
+const FULL_MAP_CELL: Vec2 = Vec2::new(60.0, 45.0);
+/// The size of the area of the full map the rooms are drawn in
+const FULL_MAP_SIZE: Vec2 = Vec2::new(900.0, 540.0);
+
+const MAP_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
+const ROOM_COLOR: Color = Color::srgb(0.45, 0.42, 0.36);
+const DISCOVERED_ROOM_COLOR: Color = Color::srgb(0.2, 0.19, 0.17);
+const CURRENT_ROOM_COLOR: Color = Color::srgb(0.867, 0.827, 0.412);
+const SELECTED_ROOM_COLOR: Color = Color::srgb(0.286, 0.478, 0.773);
+const DOOR_COLOR: Color = Color::srgb(0.925, 0.925, 0.925);
+
+/// Open while the full map is shown. Holds the floor being looked at and the room the player
+/// clicked on, if any.
+#[derive(Resource, Debug)]
+struct FullMap {
+    floor: i32,
+    selected: Option<RoomId>,
+}
+
+/// The area of the minimap that rooms are drawn in
+#[derive(Component)]
+struct MinimapRooms;
+
+/// The full map overlay
+#[derive(Component)]
+struct FullMapRoot;
+
+/// The area of the full map that rooms are drawn in
+#[derive(Component)]
+struct FullMapRooms;
+
+/// Shows the floor the full map is looking at
+#[derive(Component)]
+struct FullMapFloorLabel;
+
+/// Shows the name of the room clicked on in the full map
+#[derive(Component)]
+struct SelectedRoomLabel;
+
+/// A room on the full map that can be clicked to see its name
+#[derive(Component)]
+struct MapRoomButton(RoomId);
+
+/// A button on the full map that looks at the floor above or below
+#[derive(Component)]
+struct ChangeFloorButton(i32);
+
+fn spawn_minimap(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Minimap"),
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Px(25.0),
+                    bottom: Px(25.0),
+                    width: Px(MINIMAP_SIZE.x),
+                    height: Px(MINIMAP_SIZE.y),
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+                background_color: BackgroundColor(MAP_BACKGROUND),
+                ..default()
+            },
+            MinimapRooms,
+            StateScoped(Screen::Gameplay),
+        ))
+        .observe(open_full_map);
+}
+
+fn open_full_map(_trigger: Trigger<OnPress>, mut toggle_writer: EventWriter<ToggleFullMap>) {
+    toggle_writer.send(ToggleFullMap);
+}
+
+fn close_full_map(_trigger: Trigger<OnPress>, mut toggle_writer: EventWriter<ToggleFullMap>) {
+    toggle_writer.send(ToggleFullMap);
+}
+
+fn record_full_map_input(mut toggle_writer: EventWriter<ToggleFullMap>) {
+    toggle_writer.send(ToggleFullMap);
+}
+
+/// Opens the full map on the floor the player is on, or closes it if it is open
+#[derive(Event, Debug)]
+struct ToggleFullMap;
+
+fn toggle_full_map(
+    mut commands: Commands,
+    mut events: EventReader<ToggleFullMap>,
+    full_map: Option<Res<FullMap>>,
+    room_res: Res<MapRoomIndex>,
+    house_graph: Res<HouseGraph>,
+    rooms: Query<&Room>,
+    roots: Query<Entity, With<FullMapRoot>>,
+) {
+    // Pressing M while clicking the minimap still only toggles the map once.
+    events.clear();
+
+    if full_map.is_some() {
+        commands.remove_resource::<FullMap>();
+        for root in roots.iter() {
+            commands.entity(root).despawn_recursive();
+        }
+        return;
+    }
+
+    let floor = house_graph
+        .entity(room_res.0)
+        .and_then(|entity| rooms.get(entity).ok())
+        .map(|room| room.position.z)
+        .unwrap_or_default();
+    commands.insert_resource(FullMap {
+        floor,
+        selected: None,
+    });
+    commands
+        .ui_root()
+        .insert((
+            Name::new("Full Map"),
+            FullMapRoot,
+            BackgroundColor(MAP_BACKGROUND),
+            // Keep clicks from reaching the gameplay buttons underneath.
+            FocusPolicy::Block,
+            ZIndex::Global(1),
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|children| {
+            children.label("").insert(FullMapFloorLabel);
+            children.spawn((
+                Name::new("Full Map Rooms"),
+                NodeBundle {
+                    style: Style {
+                        width: Px(FULL_MAP_SIZE.x),
+                        height: Px(FULL_MAP_SIZE.y),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    ..default()
+                },
+                FullMapRooms,
+            ));
+            children.label("").insert(SelectedRoomLabel);
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    children
+                        .button("Floor Below")
+                        .insert(ChangeFloorButton(-1))
+                        .observe(change_floor);
+                    children
+                        .button("Floor Above")
+                        .insert(ChangeFloorButton(1))
+                        .observe(change_floor);
+                    children.button("Close").observe(close_full_map);
+                });
+        });
+}
+
+/// Whether any room has changed, such as when its doors are sealed or opened
+fn rooms_changed(rooms: Query<(), Changed<Room>>) -> bool {
+    !rooms.is_empty()
+}
+
+/// Page through the floors of the house, stopping at the top and bottom floors
+fn change_floor(
+    trigger: Trigger<OnPress>,
+    buttons: Query<&ChangeFloorButton>,
+    rooms: Query<&Room>,
+    mut full_map: ResMut<FullMap>,
+) {
+    let Ok(button) = buttons.get(trigger.entity()) else {
+        return;
+    };
+    let floors = || rooms.iter().map(|room| room.position.z);
+    let (Some(lowest), Some(highest)) = (floors().min(), floors().max()) else {
+        return;
+    };
+    let floor = (full_map.floor + button.0).clamp(lowest, highest);
+    if floor != full_map.floor {
+        full_map.floor = floor;
+        full_map.selected = None;
+    }
+}
+
+fn select_map_room(
+    trigger: Trigger<OnPress>,
+    buttons: Query<&MapRoomButton>,
+    mut full_map: ResMut<FullMap>,
+) {
+    let Ok(button) = buttons.get(trigger.entity()) else {
+        return;
+    };
+    full_map.selected = Some(button.0);
+}
+
+/// A discovered room to draw on a map
+struct MapRoom<'a> {
+    room_id: RoomId,
+    visited: bool,
+    room: &'a Room,
+    footprint: &'a [IVec2],
+}
+
+/// The discovered rooms on the given floor
+fn discovered_rooms_on_floor<'a>(
+    floor: i32,
+    houses: &Query<&House>,
+    rooms: &'a Query<&Room>,
+    explored_rooms: &ExploredRooms,
+    room_definitions: &'a RoomDefinitionManifest,
+) -> Vec<MapRoom<'a>> {
+    houses
+        .iter()
+        .flat_map(|house| house.rooms.iter())
+        .filter(|(room_id, _)| explored_rooms.is_discovered(**room_id))
+        .filter_map(|(room_id, entity)| {
+            let room = rooms.get(*entity).ok()?;
+            let definition = room_definitions.items.get(&room.room_def_id)?;
+            (room.position.z == floor).then_some(MapRoom {
+                room_id: *room_id,
+                visited: explored_rooms.is_visited(*room_id),
+                room,
+                footprint: &definition.footprint,
+            })
+        })
+        .collect()
+}
+
+/// Spawn the rooms as children of `parent`, with the cell at `center` in the middle of an area of
+/// the given size
+fn draw_rooms(
+    commands: &mut Commands,
+    parent: Entity,
+    map_rooms: &[MapRoom],
+    center: Vec2,
+    cell_size: Vec2,
+    area_size: Vec2,
+    room_color: impl Fn(RoomId) -> Color,
+    clickable: bool,
+) {
+    // Leave a gap around every room, and draw doors as notches across it.
+    let gap = (cell_size.min_element() / 10.0).max(1.0);
+    let door = gap * 2.0;
+    // The grid's y goes north while the UI's goes down.
+    let cell_top_left = |cell: IVec2| {
+        let offset = (cell.as_vec2() - center) * Vec2::new(1.0, -1.0);
+        area_size / 2.0 + offset * cell_size - cell_size / 2.0
+    };
+
+    commands.entity(parent).with_children(|children| {
+        for map_room in map_rooms {
+            let color = if map_room.visited {
+                room_color(map_room.room_id)
+            } else {
+                DISCOVERED_ROOM_COLOR
+            };
+            for cell in map_room.footprint {
+                let has_neighbour = |offset: IVec2| map_room.footprint.contains(&(*cell + offset));
+                let inset = |offset: IVec2| if has_neighbour(offset) { 0.0 } else { gap };
+                let top_left = cell_top_left(map_room.room.position.truncate() + *cell);
+                let mut node = children.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Px(top_left.x + inset(IVec2::NEG_X)),
+                        top: Px(top_left.y + inset(IVec2::Y)),
+                        width: Px(cell_size.x - inset(IVec2::NEG_X) - inset(IVec2::X)),
+                        height: Px(cell_size.y - inset(IVec2::Y) - inset(IVec2::NEG_Y)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(color),
+                    ..default()
+                });
+                // Only rooms the player has been in can be told apart.
+                if clickable && map_room.visited {
+                    node.insert((Interaction::default(), MapRoomButton(map_room.room_id)))
+                        .observe(select_map_room);
+                }
+            }
+
+            if !map_room.visited {
+                continue;
+            }
+            for exit in map_room.room.connections.keys() {
+                let top_left = cell_top_left(map_room.room.position.truncate() + exit.cell);
+                let (left, top, width, height) = match exit.direction {
+                    RoomConnectionDirection::North => {
+                        ((cell_size.x - door) / 2.0, -gap, door, gap * 2.0)
+                    }
+                    RoomConnectionDirection::South => (
+                        (cell_size.x - door) / 2.0,
+                        cell_size.y - gap,
+                        door,
+                        gap * 2.0,
+                    ),
+                    RoomConnectionDirection::West => {
+                        (-gap, (cell_size.y - door) / 2.0, gap * 2.0, door)
+                    }
+                    RoomConnectionDirection::East => (
+                        cell_size.x - gap,
+                        (cell_size.y - door) / 2.0,
+                        gap * 2.0,
+                        door,
+                    ),
+                    // Stairs are drawn as a mark inside the room, in the top or bottom corner.
+                    RoomConnectionDirection::Up => (cell_size.x - gap - door, gap, door, door),
+                    RoomConnectionDirection::Down => (gap, cell_size.y - gap - door, door, door),
+                };
+                children.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Px(top_left.x + left),
+                        top: Px(top_left.y + top),
+                        width: Px(width),
+                        height: Px(height),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(DOOR_COLOR),
+                    ..default()
+                });
+            }
+        }
+    });
+}
+
+/// The center of the current room's footprint, in grid cells
+fn room_center(map_room: &MapRoom) -> Vec2 {
+    let (sum, count) = map_room
+        .footprint
+        .iter()
+        .fold((Vec2::ZERO, 0.0_f32), |(sum, count), cell| {
+            (sum + cell.as_vec2(), count + 1.0)
+        });
+    map_room.room.position.truncate().as_vec2() + sum / count.max(1.0)
+}
+
+fn draw_minimap(
+    mut commands: Commands,
+    room_res: Res<MapRoomIndex>,
+    explored_rooms: Res<ExploredRooms>,
+    room_definitions: Option<Res<RoomDefinitionManifest>>,
+    houses: Query<&House>,
+    rooms: Query<&Room>,
+    minimaps: Query<Entity, With<MinimapRooms>>,
+) {
+    let Some(room_definitions) = room_definitions else {
+        return;
+    };
+    let Some(current_room) = houses
+        .iter()
+        .find_map(|house| house.rooms.get(&room_res.0))
+        .and_then(|entity| rooms.get(*entity).ok())
+    else {
+        return;
+    };
+    let map_rooms = discovered_rooms_on_floor(
+        current_room.position.z,
+        &houses,
+        &rooms,
+        &explored_rooms,
+        &room_definitions,
+    );
+    let center = map_rooms
+        .iter()
+        .find(|map_room| map_room.room_id == room_res.0)
+        .map(room_center)
+        .unwrap_or_default();
+
+    for minimap in minimaps.iter() {
+        commands.entity(minimap).despawn_descendants();
+        draw_rooms(
+            &mut commands,
+            minimap,
+            &map_rooms,
+            center,
+            MINIMAP_CELL,
+            MINIMAP_SIZE,
+            |room_id| {
+                if room_id == room_res.0 {
+                    CURRENT_ROOM_COLOR
+                } else {
+                    ROOM_COLOR
+                }
+            },
+            false,
+        );
+    }
+}
+
+fn draw_full_map(
+    mut commands: Commands,
+    full_map: Res<FullMap>,
+    room_res: Res<MapRoomIndex>,
+    explored_rooms: Res<ExploredRooms>,
+    room_definitions: Option<Res<RoomDefinitionManifest>>,
+    houses: Query<&House>,
+    rooms: Query<&Room>,
+    maps: Query<Entity, With<FullMapRooms>>,
+    mut floor_labels: Query<&mut Text, (With<FullMapFloorLabel>, Without<SelectedRoomLabel>)>,
+    mut selected_labels: Query<&mut Text, (With<SelectedRoomLabel>, Without<FullMapFloorLabel>)>,
+) {
+    let Some(room_definitions) = room_definitions else {
+        return;
+    };
+    let map_rooms = discovered_rooms_on_floor(
+        full_map.floor,
+        &houses,
+        &rooms,
+        &explored_rooms,
+        &room_definitions,
+    );
+    // Center the map on the middle of the rooms seen on this floor.
+    let (min, max) = map_rooms
+        .iter()
+        .flat_map(|map_room| {
+            map_room
+                .footprint
+                .iter()
+                .map(|cell| (map_room.room.position.truncate() + *cell).as_vec2())
+        })
+        .fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), cell| (min.min(cell), max.max(cell)),
+        );
+    let center = if map_rooms.is_empty() {
+        Vec2::ZERO
+    } else {
+        (min + max) / 2.0
+    };
+
+    for map in maps.iter() {
+        commands.entity(map).despawn_descendants();
+        draw_rooms(
+            &mut commands,
+            map,
+            &map_rooms,
+            center,
+            FULL_MAP_CELL,
+            FULL_MAP_SIZE,
+            |room_id| {
+                if Some(room_id) == full_map.selected {
+                    SELECTED_ROOM_COLOR
+                } else if room_id == room_res.0 {
+                    CURRENT_ROOM_COLOR
+                } else {
+                    ROOM_COLOR
+                }
+            },
+            true,
+        );
+    }
+
+    let floor_name = floor_name(full_map.floor);
+    for mut text in floor_labels.iter_mut() {
+        text.sections[0].value.clone_from(&floor_name);
+    }
+
+    let selected_name = full_map
+        .selected
+        .and_then(|room_id| {
+            map_rooms
+                .iter()
+                .find(|map_room| map_room.room_id == room_id)
+        })
+        .and_then(|map_room| room_definitions.items.get(&map_room.room.room_def_id))
+        .map(|definition| definition.room_name.clone())
+        .unwrap_or_else(|| "Click a room to see what it is".to_string());
+    for mut text in selected_labels.iter_mut() {
+        text.sections[0].value.clone_from(&selected_name);
+    }
+}
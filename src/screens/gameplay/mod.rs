@@ -10,11 +10,28 @@ use bevy_asset_loader::{
 };
 
 mod gameplay_ui;
+mod inventory_panel;
+mod minimap;
+mod pause_menu;
+mod sanity_meter;
 
-use crate::{audio::Music, map::GenerateMap, screens::Screen, AppLoadingState};
+use crate::{
+    audio::Music,
+    map::{GenerateMap, HandMadeHouse, HouseAsset, LoadHouse, MapGenerationFailed, MapRoomIndex},
+    monster::PlaceMonsters,
+    save::ResumeRun,
+    screens::Screen,
+    AppLoadingState,
+};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(gameplay_ui::plugin);
+    app.add_plugins((
+        gameplay_ui::plugin,
+        inventory_panel::plugin,
+        minimap::plugin,
+        pause_menu::plugin,
+        sanity_meter::plugin,
+    ));
     app.add_systems(OnEnter(Screen::Gameplay), spawn_gameplay_level);
     app.configure_loading_state(
         LoadingStateConfig::new(AppLoadingState::Loading).load_collection::<GameplayMusic>(),
@@ -27,10 +44,51 @@ pub(super) fn plugin(app: &mut App) {
         return_to_title_screen
             .run_if(in_state(Screen::Gameplay).and_then(input_just_pressed(KeyCode::Escape))),
     );
+    app.add_systems(
+        Update,
+        leave_failed_map_generation
+            .run_if(in_state(Screen::Gameplay).and_then(on_event::<MapGenerationFailed>())),
+    );
 }
 
-fn spawn_gameplay_level(mut commands: Commands) {
-    commands.add(GenerateMap { room_count: 10 });
+fn spawn_gameplay_level(
+    mut commands: Commands,
+    resume: Option<Res<ResumeRun>>,
+    hand_made_house: Option<Res<HandMadeHouse>>,
+    houses: Res<Assets<HouseAsset>>,
+) {
+    if let Some(resume) = resume {
+        // The player starts off in the room they left, rather than sliding over to it.
+        commands.insert_resource(MapRoomIndex(resume.0.current_room));
+        commands.add(LoadHouse {
+            layout: resume.0.house.clone(),
+            new_run: false,
+        });
+        commands.add(PlaceMonsters {
+            saved: resume.0.monsters.clone(),
+        });
+        return;
+    }
+    if let Some(hand_made_house) = hand_made_house {
+        commands.remove_resource::<HandMadeHouse>();
+        if let Some(house) = houses.get(&hand_made_house.0) {
+            commands.add(LoadHouse {
+                layout: house.0.clone(),
+                new_run: true,
+            });
+            commands.add(PlaceMonsters { saved: None });
+            return;
+        }
+        error!("The hand-made house isn't loaded, so a new one will be generated");
+    }
+    commands.add(GenerateMap {
+        room_count: 10,
+        floors_above: 1,
+        floors_below: 1,
+        locked_doors: 2,
+        seed: None,
+    });
+    commands.add(PlaceMonsters { saved: None });
 }
 
 #[derive(Resource, AssetCollection, Reflect, Clone)]
@@ -63,3 +121,16 @@ fn stop_music(mut commands: Commands, mut music: ResMut<GameplayMusic>) {
 fn return_to_title_screen(mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
+
+fn leave_failed_map_generation(
+    mut events: EventReader<MapGenerationFailed>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    for event in events.read() {
+        error!(
+            "Failed to spawn a house with seed {:?}: {}",
+            event.seed, event.error
+        );
+    }
+    next_screen.set(Screen::Title);
+}
@@ -0,0 +1,60 @@
+//! Pausing the game with the P key, which stops the clock and covers the house until it is resumed.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, ui::FocusPolicy};
+
+use crate::{
+    screens::{Pause, Screen},
+    theme::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        toggle_pause
+            .run_if(in_state(Screen::Gameplay).and_then(input_just_pressed(KeyCode::KeyP)))
+            .in_set(AppSet::RecordInput),
+    );
+    app.add_systems(OnEnter(Pause::Paused), (stop_time, spawn_pause_menu));
+    app.add_systems(OnExit(Pause::Paused), start_time);
+}
+
+const MENU_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
+
+fn toggle_pause(pause: Res<State<Pause>>, mut next_pause: ResMut<NextState<Pause>>) {
+    next_pause.set(match pause.get() {
+        Pause::Running => Pause::Paused,
+        Pause::Paused => Pause::Running,
+    });
+}
+
+/// Stop everything that runs on the game's time, such as room transitions and corruption
+fn stop_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn start_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert((
+            Name::new("Pause Menu"),
+            BackgroundColor(MENU_BACKGROUND),
+            // Keep clicks from reaching the gameplay buttons and props underneath.
+            FocusPolicy::Block,
+            Interaction::None,
+            ZIndex::Global(2),
+            StateScoped(Pause::Paused),
+        ))
+        .with_children(|children| {
+            children.header("Paused");
+            children.button("Resume").observe(resume);
+        });
+}
+
+fn resume(_trigger: Trigger<OnPress>, mut next_pause: ResMut<NextState<Pause>>) {
+    next_pause.set(Pause::Running);
+}
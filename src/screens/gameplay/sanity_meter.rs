@@ -0,0 +1,110 @@
+//! A meter showing how much sanity the player has left, and a haze over the house that thickens as
+//! they lose it.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{
+    sanity::{CurrentSanityLevel, Sanity, SanityLevel},
+    screens::Screen,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_sanity_meter);
+    app.add_systems(
+        Update,
+        (
+            update_sanity_meter.run_if(resource_changed::<Sanity>),
+            distort_vision.run_if(resource_changed::<CurrentSanityLevel>),
+        )
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSet::Update),
+    );
+}
+
+const METER_SIZE: Vec2 = Vec2::new(200.0, 16.0);
+const METER_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
+const METER_FILL: Color = Color::srgb(0.478, 0.365, 0.671);
+
+/// The part of the meter that shrinks as sanity is lost
+#[derive(Component)]
+struct SanityMeterFill;
+
+/// A tint over the whole screen that darkens as sanity is lost
+#[derive(Component)]
+struct SanityHaze;
+
+fn spawn_sanity_meter(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Sanity Meter"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    // Just below the floor label.
+                    top: Px(60.0),
+                    left: Px(25.0),
+                    width: Px(METER_SIZE.x),
+                    height: Px(METER_SIZE.y),
+                    ..default()
+                },
+                background_color: BackgroundColor(METER_BACKGROUND),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Sanity Meter Fill"),
+                NodeBundle {
+                    style: Style {
+                        width: Percent(100.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(METER_FILL),
+                    ..default()
+                },
+                SanityMeterFill,
+            ));
+        });
+
+    commands.spawn((
+        Name::new("Sanity Haze"),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Percent(100.0),
+                height: Percent(100.0),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::NONE),
+            // Over the house, but under the rest of the UI.
+            z_index: ZIndex::Global(-1),
+            ..default()
+        },
+        SanityHaze,
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn update_sanity_meter(sanity: Res<Sanity>, mut fills: Query<&mut Style, With<SanityMeterFill>>) {
+    for mut style in fills.iter_mut() {
+        style.width = Percent(sanity.fraction() * 100.0);
+    }
+}
+
+fn distort_vision(
+    current_level: Res<CurrentSanityLevel>,
+    mut hazes: Query<&mut BackgroundColor, With<SanityHaze>>,
+) {
+    let alpha = match current_level.level() {
+        SanityLevel::Lucid => 0.0,
+        SanityLevel::Uneasy => 0.12,
+        SanityLevel::Shaken => 0.25,
+        SanityLevel::Unravelling => 0.4,
+    };
+    for mut haze in hazes.iter_mut() {
+        haze.0 = Color::srgba(0.15, 0.0, 0.2, alpha);
+    }
+}
@@ -1,8 +1,10 @@
 //! The game's main screen states and transitions between them.
 
 mod credits;
+mod game_over;
 mod gameplay;
 mod loading;
+mod settings;
 mod splash;
 mod title;
 
@@ -11,11 +13,15 @@ use bevy::prelude::*;
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<Screen>();
     app.enable_state_scoped_entities::<Screen>();
+    app.add_sub_state::<Pause>();
+    app.enable_state_scoped_entities::<Pause>();
 
     app.add_plugins((
         credits::plugin,
+        game_over::plugin,
         gameplay::plugin,
         loading::plugin,
+        settings::plugin,
         splash::plugin,
         title::plugin,
     ));
@@ -29,5 +35,16 @@ pub enum Screen {
     Splash,
     Title,
     Credits,
+    Settings,
     Gameplay,
+    GameOver,
+}
+
+/// Whether gameplay is paused. Only exists during [`Screen::Gameplay`].
+#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Default)]
+#[source(Screen = Screen::Gameplay)]
+pub enum Pause {
+    #[default]
+    Running,
+    Paused,
 }
@@ -0,0 +1,113 @@
+//! A settings screen that can be accessed from the title screen, where the controls can be changed.
+
+use bevy::prelude::*;
+
+use crate::{
+    input::{InputBindings, Rebinding},
+    map::RoomConnectionDirection,
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Settings), spawn_settings_screen);
+    app.add_systems(OnExit(Screen::Settings), |mut commands: Commands| {
+        commands.remove_resource::<Rebinding>();
+    });
+    app.add_systems(
+        Update,
+        update_binding_labels.run_if(in_state(Screen::Settings)),
+    );
+}
+
+const DIRECTIONS: [RoomConnectionDirection; 6] = [
+    RoomConnectionDirection::North,
+    RoomConnectionDirection::East,
+    RoomConnectionDirection::South,
+    RoomConnectionDirection::West,
+    RoomConnectionDirection::Up,
+    RoomConnectionDirection::Down,
+];
+
+fn spawn_settings_screen(mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Settings))
+        .with_children(|children| {
+            children.header("Controls");
+            children.label("Click a control, then press the key or gamepad input to use for it.");
+            for direction in DIRECTIONS {
+                children
+                    .button("")
+                    .insert(BindingButton(direction))
+                    .observe(start_rebinding);
+            }
+
+            children.button("Reset Controls").observe(reset_bindings);
+            children.button("Back").observe(enter_title_screen);
+        });
+}
+
+/// A button showing the bindings for a direction, which rebinds it when pressed
+#[derive(Component, Debug)]
+struct BindingButton(RoomConnectionDirection);
+
+fn start_rebinding(
+    trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    buttons: Query<&BindingButton>,
+) {
+    let Ok(button) = buttons.get(trigger.entity()) else {
+        return;
+    };
+    commands.insert_resource(Rebinding(button.0.clone()));
+}
+
+fn reset_bindings(
+    _trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut bindings: ResMut<InputBindings>,
+) {
+    *bindings = InputBindings::default();
+    commands.remove_resource::<Rebinding>();
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
+
+fn update_binding_labels(
+    bindings: Res<InputBindings>,
+    rebinding: Option<Res<Rebinding>>,
+    buttons: Query<(&BindingButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in buttons.iter() {
+        let action = match button.0 {
+            RoomConnectionDirection::Up | RoomConnectionDirection::Down => {
+                format!("Go {:?}", button.0)
+            }
+            _ => format!("Move {:?}", button.0),
+        };
+        let is_rebinding = rebinding
+            .as_ref()
+            .is_some_and(|rebinding| rebinding.0 == button.0);
+        let label = if is_rebinding {
+            format!("{action}: press an input, or Escape to cancel")
+        } else {
+            let inputs: Vec<String> = bindings
+                .get(&button.0)
+                .iter()
+                .map(|binding| binding.label())
+                .collect();
+            format!("{action}: {}", inputs.join(", "))
+        };
+
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.sections[0].value != label {
+                text.sections[0].value.clone_from(&label);
+            }
+        }
+    }
+}
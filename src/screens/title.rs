@@ -2,7 +2,12 @@
 
 use bevy::prelude::*;
 
-use crate::{screens::Screen, theme::prelude::*};
+use crate::{
+    map::{HandMadeHouse, HouseAssets},
+    save::{has_saved_run, load_run, ResumeRun},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), spawn_title_screen);
@@ -13,7 +18,12 @@ fn spawn_title_screen(mut commands: Commands) {
         .ui_root()
         .insert(StateScoped(Screen::Title))
         .with_children(|children| {
+            if has_saved_run() {
+                children.button("Continue").observe(continue_run);
+            }
             children.button("Play").observe(enter_gameplay_screen);
+            children.button("Play the Manor").observe(play_manor);
+            children.button("Settings").observe(enter_settings_screen);
             children.button("Credits").observe(enter_credits_screen);
 
             #[cfg(not(target_family = "wasm"))]
@@ -25,6 +35,35 @@ fn enter_gameplay_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<Nex
     next_screen.set(Screen::Gameplay);
 }
 
+fn play_manor(
+    _trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    house_assets: Res<HouseAssets>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    commands.insert_resource(HandMadeHouse(house_assets.manor.clone()));
+    next_screen.set(Screen::Gameplay);
+}
+
+fn continue_run(
+    _trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    match load_run() {
+        Ok(Some(run)) => {
+            commands.insert_resource(ResumeRun(run));
+            next_screen.set(Screen::Gameplay);
+        }
+        Ok(None) => {}
+        Err(error) => error!("Failed to load the saved run: {error}"),
+    }
+}
+
+fn enter_settings_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Settings);
+}
+
 fn enter_credits_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Credits);
 }
@@ -0,0 +1,57 @@
+//! The player's settings, kept between sessions next to the saved run.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::InputBindings,
+    save::{storage, SaveError},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let settings = match load_settings() {
+        Ok(settings) => settings.unwrap_or_default(),
+        Err(error) => {
+            warn!("Failed to load the settings, using the defaults: {error}");
+            Settings::default()
+        }
+    };
+    app.insert_resource(settings.input_bindings);
+
+    app.add_systems(
+        Update,
+        save_settings.run_if(
+            resource_changed::<InputBindings>.and_then(not(resource_added::<InputBindings>)),
+        ),
+    );
+}
+
+/// What the settings are stored under.
+const SETTINGS_NAME: &str = "settings";
+
+/// Everything the player can change in the settings. Anything missing from the stored settings,
+/// such as settings added since they were written, is left at its default.
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+struct Settings {
+    input_bindings: InputBindings,
+}
+
+fn load_settings() -> Result<Option<Settings>, SaveError> {
+    let Some(json) = storage::read(SETTINGS_NAME)? else {
+        return Ok(None);
+    };
+    Ok(Some(serde_json::from_str(&json)?))
+}
+
+fn save_settings(input_bindings: Res<InputBindings>) {
+    let settings = Settings {
+        input_bindings: input_bindings.clone(),
+    };
+    let result = serde_json::to_string(&settings)
+        .map_err(SaveError::from)
+        .and_then(|json| storage::write(SETTINGS_NAME, &json));
+    if let Err(error) = result {
+        error!("Failed to save the settings: {error}");
+    }
+}
@@ -0,0 +1,142 @@
+//! Turns and the in-game clock.
+//!
+//! Nothing in the house happens on its own: time only moves on when the player does something.
+//! Walking into a room or taking an action ends a turn and winds the [`GameClock`] forward by however
+//! long it took, after which a [`TurnEnded`] is sent. Systems that should happen once per turn can
+//! run with the [`turn_ended`] run condition. No turns end while the game is paused.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    inventory::UseItem,
+    map::{OnInteract, RoomTraversed},
+    screens::{Pause, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GameClock>();
+    app.init_resource::<GameClock>();
+    app.add_systems(OnEnter(Screen::Gameplay), |mut commands: Commands| {
+        commands.insert_resource(GameClock::default());
+    });
+    app.add_event::<EndTurn>();
+    app.add_event::<TurnEnded>();
+    app.observe(end_turn_on_interact);
+
+    app.add_systems(
+        Update,
+        (
+            (
+                end_turn_on_traversal.run_if(on_event::<RoomTraversed>()),
+                end_turn_on_item_use.run_if(on_event::<UseItem>()),
+            ),
+            advance_clock.run_if(on_event::<EndTurn>()),
+            log_turns.run_if(turn_ended),
+        )
+            .chain()
+            .run_if(in_state(Pause::Running))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// The time on the clock when a run starts, in minutes after midnight
+const START_OF_RUN: u32 = 21 * 60;
+/// How many minutes walking into another room takes
+const ROOM_MINUTES: u32 = 5;
+/// How many minutes interacting with a prop or using an item takes
+const ACTION_MINUTES: u32 = 1;
+
+/// How many turns the player has taken, and how much time has passed in the house because of them
+#[derive(Resource, Reflect, Serialize, Deserialize, Debug, Default, Clone, Copy)]
+#[reflect(Resource)]
+pub struct GameClock {
+    turn: u32,
+    minutes: u32,
+}
+
+impl GameClock {
+    /// The number of turns that have ended since the run started
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    /// The hour and minute shown on the clock
+    pub fn time_of_day(&self) -> (u32, u32) {
+        let time = START_OF_RUN + self.minutes;
+        ((time / 60) % 24, time % 60)
+    }
+
+    fn advance(&mut self, minutes: u32) {
+        self.turn += 1;
+        self.minutes += minutes;
+    }
+}
+
+/// Ends the current turn, moving the clock forward by the given number of minutes
+#[derive(Event, Debug)]
+pub struct EndTurn {
+    pub minutes: u32,
+}
+
+/// Sent after the [`GameClock`] has moved on to the next turn
+#[derive(Event, Debug)]
+pub struct TurnEnded {
+    /// The turn that just ended, counting from `1`
+    pub turn: u32,
+}
+
+/// Run condition for systems that should run once each time a turn ends
+pub fn turn_ended(mut events: EventReader<TurnEnded>) -> bool {
+    events.read().count() > 0
+}
+
+/// Walking into a room takes a turn. Being put back into a room when a run is resumed doesn't.
+fn end_turn_on_traversal(
+    mut events: EventReader<RoomTraversed>,
+    mut turn_writer: EventWriter<EndTurn>,
+) {
+    for _ in events.read() {
+        turn_writer.send(EndTurn {
+            minutes: ROOM_MINUTES,
+        });
+    }
+}
+
+fn end_turn_on_item_use(mut events: EventReader<UseItem>, mut turn_writer: EventWriter<EndTurn>) {
+    for _ in events.read() {
+        turn_writer.send(EndTurn {
+            minutes: ACTION_MINUTES,
+        });
+    }
+}
+
+fn end_turn_on_interact(
+    _trigger: Trigger<OnInteract>,
+    pause: Option<Res<State<Pause>>>,
+    mut turn_writer: EventWriter<EndTurn>,
+) {
+    if pause.is_some_and(|pause| *pause.get() == Pause::Running) {
+        turn_writer.send(EndTurn {
+            minutes: ACTION_MINUTES,
+        });
+    }
+}
+
+fn advance_clock(
+    mut events: EventReader<EndTurn>,
+    mut clock: ResMut<GameClock>,
+    mut turn_writer: EventWriter<TurnEnded>,
+) {
+    for event in events.read() {
+        clock.advance(event.minutes);
+        turn_writer.send(TurnEnded { turn: clock.turn });
+    }
+}
+
+fn log_turns(mut events: EventReader<TurnEnded>) {
+    for event in events.read() {
+        debug!("Turn {} ended", event.turn);
+    }
+}